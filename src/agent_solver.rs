@@ -0,0 +1,371 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::MazeError;
+use crate::util::*;
+use crate::{Maze, Pair};
+
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExplorationTrace {
+    // Every cell the strategy touched, in order and with repeats. Walkers record each step they
+    // take, dead-end filling records each cell it fills.
+    pub steps: Vec<Coord>,
+    // Route from start to finish that the strategy ends up with.
+    pub path: Vec<Coord>,
+}
+
+impl ExplorationTrace {
+    #[allow(unused)]
+    pub fn visited_cells(&self) -> HashSet<Coord> {
+        self.steps.iter().copied().collect()
+    }
+
+    // Share of the maze's cells the strategy touched at least once.
+    #[allow(unused)]
+    pub fn coverage(&self, maze: &Maze) -> f32 {
+        self.visited_cells().len() as f32 / (maze.width * maze.height) as f32
+    }
+}
+
+// Solvers that only see the walls around the cell they are standing on, the way a person or a
+// robot walking the maze would.
+pub struct AgentSolver;
+
+impl AgentSolver {
+    // Keeps one hand on the wall. Only guaranteed to succeed when the start and the finish are
+    // connected to the same wall, which is always the case in a perfect maze.
+    #[allow(unused)]
+    pub fn wall_follower_solver(
+        maze: &Maze,
+        start: Coord,
+        finish: Coord,
+        hand: Hand,
+    ) -> Result<ExplorationTrace, MazeError> {
+        AgentSolver::check_endpoints(maze, start, finish)?;
+
+        // Relative to the heading: hand side first, then straight, the other side and back.
+        let turns = match hand {
+            Hand::Left => [3, 0, 1, 2],
+            Hand::Right => [1, 0, 3, 2],
+        };
+
+        let mut steps = vec![start];
+        let mut current_coord = start;
+        let mut heading = NORTH;
+        let mut seen_states: HashSet<(Coord, usize)> = HashSet::new();
+
+        while current_coord != finish {
+            // Being back in the same spot facing the same way means the walk is going in circles.
+            if !seen_states.insert((current_coord, heading)) {
+                return Err(MazeError::NoPath { start, finish });
+            }
+
+            let next_step = turns.iter().find_map(|turn| {
+                let dir = (heading + turn) % 4;
+                AgentSolver::step(maze, current_coord, dir).map(|coord| (dir, coord))
+            });
+
+            match next_step {
+                Some((dir, coord)) => {
+                    heading = dir;
+                    current_coord = coord;
+                    steps.push(coord);
+                }
+                None => return Err(MazeError::NoPath { start, finish }),
+            }
+        }
+
+        Ok(ExplorationTrace {
+            path: AgentSolver::loop_erased_path(&steps),
+            steps,
+        })
+    }
+
+    // Trémaux's algorithm: every passage gets a mark each time it is walked and is never walked
+    // more than twice. Finds the finish whenever it is reachable, in any maze.
+    #[allow(unused)]
+    pub fn tremaux_solver(
+        maze: &Maze,
+        start: Coord,
+        finish: Coord,
+    ) -> Result<ExplorationTrace, MazeError> {
+        AgentSolver::check_endpoints(maze, start, finish)?;
+
+        let mut marks: HashMap<(Coord, Coord), u8> = HashMap::new();
+        let passage = |a: Coord, b: Coord| if a < b { (a, b) } else { (b, a) };
+
+        let mut steps = vec![start];
+        let mut visited: HashSet<Coord> = HashSet::from([start]);
+        let mut current_coord = start;
+        let mut previous_coord: Option<Coord> = None;
+        let mut is_revisit = false;
+
+        while current_coord != finish {
+            let exits: Vec<Coord> = (0..4)
+                .filter_map(|dir| AgentSolver::step(maze, current_coord, dir))
+                .collect();
+            let mark_count = |marks: &HashMap<(Coord, Coord), u8>, coord: Coord| {
+                *marks.get(&passage(current_coord, coord)).unwrap_or(&0)
+            };
+
+            // Walked into a known cell through a fresh passage: turn around.
+            let turn_back = previous_coord
+                .filter(|previous_coord| is_revisit && mark_count(&marks, *previous_coord) == 1);
+
+            let next_coord = turn_back
+                .or_else(|| {
+                    exits
+                        .iter()
+                        .filter(|coord| Some(**coord) != previous_coord)
+                        .filter(|coord| mark_count(&marks, **coord) < 2)
+                        .min_by_key(|coord| mark_count(&marks, **coord))
+                        .copied()
+                })
+                .or_else(|| previous_coord.filter(|coord| mark_count(&marks, *coord) < 2));
+
+            let next_coord = match next_coord {
+                Some(coord) => coord,
+                None => return Err(MazeError::NoPath { start, finish }),
+            };
+
+            *marks.entry(passage(current_coord, next_coord)).or_insert(0) += 1;
+            previous_coord = Some(current_coord);
+            current_coord = next_coord;
+            is_revisit = !visited.insert(current_coord);
+            steps.push(current_coord);
+        }
+
+        Ok(ExplorationTrace {
+            path: AgentSolver::loop_erased_path(&steps),
+            steps,
+        })
+    }
+
+    // Fills in dead ends until only the corridors between start and finish are left. Looks at the
+    // whole maze at once, the way someone solving it on paper would.
+    #[allow(unused)]
+    pub fn dead_end_filling_solver(
+        maze: &Maze,
+        start: Coord,
+        finish: Coord,
+    ) -> Result<ExplorationTrace, MazeError> {
+        AgentSolver::check_endpoints(maze, start, finish)?;
+
+        let open_neighbours = |coord: Coord| -> Vec<Coord> {
+            (0..4)
+                .filter_map(|dir| AgentSolver::step(maze, coord, dir))
+                .collect()
+        };
+
+        let mut exit_counts: HashMap<Coord, usize> = HashMap::new();
+        let mut work_queue: VecDeque<Coord> = VecDeque::new();
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let coord = Pair::new(x, y);
+                let exit_count = open_neighbours(coord).len();
+                exit_counts.insert(coord, exit_count);
+
+                if exit_count <= 1 && coord != start && coord != finish {
+                    work_queue.push_back(coord);
+                }
+            }
+        }
+
+        let mut filled: HashSet<Coord> = HashSet::new();
+        let mut steps: Vec<Coord> = vec![];
+        while let Some(current_coord) = work_queue.pop_front() {
+            if !filled.insert(current_coord) {
+                continue;
+            }
+            steps.push(current_coord);
+
+            for neighbour_coord in open_neighbours(current_coord) {
+                if filled.contains(&neighbour_coord) {
+                    continue;
+                }
+
+                let exit_count = exit_counts
+                    .get_mut(&neighbour_coord)
+                    .expect("Cell not found");
+                *exit_count -= 1;
+
+                if *exit_count <= 1 && neighbour_coord != start && neighbour_coord != finish {
+                    work_queue.push_back(neighbour_coord);
+                }
+            }
+        }
+
+        // In a perfect maze the cells left over are exactly the solution. Loops survive the
+        // filling, so pick the shortest way through whatever is left.
+        let mut previous_coords: HashMap<Coord, Coord> = HashMap::new();
+        let mut work_queue: VecDeque<Coord> = VecDeque::from([start]);
+        while let Some(current_coord) = work_queue.pop_front() {
+            if current_coord == finish {
+                break;
+            }
+
+            for neighbour_coord in open_neighbours(current_coord) {
+                if neighbour_coord != start
+                    && !filled.contains(&neighbour_coord)
+                    && !previous_coords.contains_key(&neighbour_coord)
+                {
+                    previous_coords.insert(neighbour_coord, current_coord);
+                    work_queue.push_back(neighbour_coord);
+                }
+            }
+        }
+
+        let mut path = vec![finish];
+        let mut current_coord = finish;
+        while current_coord != start {
+            current_coord = *previous_coords
+                .get(&current_coord)
+                .ok_or(MazeError::NoPath { start, finish })?;
+            path.push(current_coord);
+        }
+        path.reverse();
+
+        Ok(ExplorationTrace { steps, path })
+    }
+
+    fn check_endpoints(maze: &Maze, start: Coord, finish: Coord) -> Result<(), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        Ok(())
+    }
+
+    // Neighbour in direction `dir`, if there is one and no wall in between.
+    fn step(maze: &Maze, coord: Coord, dir: usize) -> Option<Coord> {
+        let neighbour_coord = Pair::new(
+            coord.x as i32 + NEIGHBOUR_MAP[dir][0],
+            coord.y as i32 + NEIGHBOUR_MAP[dir][1],
+        );
+
+        if neighbour_coord.x < 0
+            || neighbour_coord.y < 0
+            || neighbour_coord.x >= maze.width as i32
+            || neighbour_coord.y >= maze.height as i32
+            || maze.cells[coord.index(maze.width)].paths[dir]
+        {
+            return None;
+        }
+
+        Some(neighbour_coord.to_usize())
+    }
+
+    // Walk with every detour cut out: stepping onto a cell already on the path drops whatever was
+    // walked since the last time there.
+    fn loop_erased_path(steps: &[Coord]) -> Vec<Coord> {
+        let mut path: Vec<Coord> = vec![];
+
+        for coord in steps {
+            match path.iter().position(|path_coord| path_coord == coord) {
+                Some(i) => path.truncate(i + 1),
+                None => path.push(*coord),
+            }
+        }
+
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze_builder::MazeBuilder;
+    use crate::solver::Solver;
+
+    fn seeded_maze(seed: u64) -> Maze {
+        let mut maze = Maze::new_full(9, 7);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, seed).unwrap();
+
+        maze
+    }
+
+    // Every step goes to a neighbour through an open side.
+    fn assert_walked(maze: &Maze, steps: &[Coord]) {
+        for step in steps.windows(2) {
+            assert!((0..4).any(|dir| AgentSolver::step(maze, step[0], dir) == Some(step[1])));
+        }
+    }
+
+    #[test]
+    fn solves_perfect_mazes() {
+        let (start, finish) = (Pair::new(0, 0), Pair::new(8, 6));
+
+        for seed in 0..5 {
+            let maze = seeded_maze(seed);
+            let solution = Solver::dijkstra_path_finding_solver(&maze, start, finish).unwrap();
+
+            for trace in [
+                AgentSolver::wall_follower_solver(&maze, start, finish, Hand::Left).unwrap(),
+                AgentSolver::wall_follower_solver(&maze, start, finish, Hand::Right).unwrap(),
+                AgentSolver::tremaux_solver(&maze, start, finish).unwrap(),
+            ] {
+                assert_eq!(trace.steps.first(), Some(&start));
+                assert_eq!(trace.steps.last(), Some(&finish));
+                assert_walked(&maze, &trace.steps);
+                assert_eq!(trace.path, solution);
+            }
+
+            let trace = AgentSolver::dead_end_filling_solver(&maze, start, finish).unwrap();
+            assert_eq!(trace.path, solution);
+            assert_eq!(trace.steps.len(), 9 * 7 - solution.len());
+            assert!(trace.steps.iter().all(|coord| !solution.contains(coord)));
+        }
+    }
+
+    #[test]
+    fn finds_the_finish_away_from_the_walls_with_tremaux() {
+        // Nothing but open floor, the centre touches no wall.
+        let maze = Maze::new_empty(3, 3);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(1, 1));
+
+        // The left hand keeps to the outer wall and goes round it for ever.
+        assert_eq!(
+            AgentSolver::wall_follower_solver(&maze, start, finish, Hand::Left)
+                .map(|trace| trace.path),
+            Err(MazeError::NoPath { start, finish })
+        );
+
+        let trace = AgentSolver::tremaux_solver(&maze, start, finish).unwrap();
+        assert_eq!(trace.steps.last(), Some(&finish));
+        assert_walked(&maze, &trace.steps);
+
+        let trace = AgentSolver::dead_end_filling_solver(&maze, start, finish).unwrap();
+        assert_eq!(trace.steps, vec![]);
+        assert_eq!(trace.path.len(), 3);
+        assert_walked(&maze, &trace.path);
+    }
+
+    #[test]
+    fn reports_unreachable_finishes() {
+        let mut maze = Maze::new_full(3, 1);
+        maze.connect_cells(0, 0, EAST);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(2, 0));
+        let no_path = Err(MazeError::NoPath { start, finish });
+
+        assert_eq!(
+            AgentSolver::wall_follower_solver(&maze, start, finish, Hand::Left)
+                .map(|trace| trace.path),
+            no_path
+        );
+        assert_eq!(
+            AgentSolver::tremaux_solver(&maze, start, finish).map(|trace| trace.path),
+            no_path
+        );
+        assert_eq!(
+            AgentSolver::dead_end_filling_solver(&maze, start, finish).map(|trace| trace.path),
+            no_path
+        );
+    }
+}
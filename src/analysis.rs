@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::circle_maze::CircleMaze;
+use crate::error::MazeError;
+use crate::solver::Solver;
+use crate::util::*;
+use crate::{Maze, Pair};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MazeReport {
+    pub cell_count: usize,
+    pub dead_ends: usize,
+    pub three_way_junctions: usize,
+    // Cells with four or more exits (the centre of a circle maze can have up to six).
+    pub four_way_junctions: usize,
+    // Corridor length (cells with exactly two exits in a row) -> number of such corridors.
+    pub corridor_lengths: BTreeMap<usize, usize>,
+    // Share of cells that are part of a corridor. Mazes with long winding passages and few
+    // branches score high.
+    pub river_factor: f32,
+    // Steps from start to finish.
+    pub solution_length: usize,
+    // Cells on the solution, finish excluded, where more than one way leads on.
+    pub solution_decision_points: usize,
+    // Deepest a dead branch reaches away from the solution, in steps.
+    pub longest_dead_branch: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AveragedMazeReport {
+    pub runs: usize,
+    pub cell_count: f32,
+    pub dead_ends: f32,
+    pub three_way_junctions: f32,
+    pub four_way_junctions: f32,
+    pub corridor_lengths: BTreeMap<usize, f32>,
+    pub river_factor: f32,
+    pub solution_length: f32,
+    pub solution_decision_points: f32,
+    pub longest_dead_branch: f32,
+}
+
+pub struct Analysis;
+
+impl Analysis {
+    #[allow(unused)]
+    pub fn analyse_maze(maze: &Maze, start: Coord, finish: Coord) -> Result<MazeReport, MazeError> {
+        let mut links: HashMap<Coord, Vec<Coord>> = HashMap::new();
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let coord = Pair::new(x, y);
+                let cell = maze.cell_at(coord)?;
+
+                links.insert(
+                    coord,
+                    maze.neighbours(coord, CellReachType::Anything)
+                        .into_iter()
+                        .filter(|(dir, _)| !cell.paths[*dir])
+                        .map(|(_, neighbour_coord)| neighbour_coord)
+                        .collect(),
+                );
+            }
+        }
+
+        let (_, distance_map) = Solver::build_distance_map(maze, start)?;
+        let solution = Solver::dijkstra_path_finding_solver(maze, start, finish)?;
+
+        Ok(Analysis::build_report(&links, &distance_map, &solution))
+    }
+
+    #[allow(unused)]
+    pub fn analyse_circle_maze(
+        maze: &CircleMaze,
+        start: Coord,
+        finish: Coord,
+    ) -> Result<MazeReport, MazeError> {
+        let mut links: HashMap<Coord, Vec<Coord>> = HashMap::new();
+        for y in 0..maze.height {
+            for x in 0..maze.cells[y].len() {
+                let coord = Pair::new(x, y);
+                let cell = maze.cell_at(coord)?;
+
+                links.insert(
+                    coord,
+                    maze.neighbours(coord, CellReachType::Anything)
+                        .into_iter()
+                        .filter(|(dir, _)| cell.is_open_at(*dir))
+                        .map(|(_, neighbour_coord)| neighbour_coord)
+                        .collect(),
+                );
+            }
+        }
+
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(maze, start)?;
+        let solution = Solver::dijkstra_path_finding_solver_for_circle_maze(maze, start, finish)?;
+
+        Ok(Analysis::build_report(&links, &distance_map, &solution))
+    }
+
+    #[allow(unused)]
+    pub fn average(reports: &[MazeReport]) -> AveragedMazeReport {
+        let runs = reports.len();
+        let mean = |value: fn(&MazeReport) -> f32| {
+            if runs == 0 {
+                0.0
+            } else {
+                reports.iter().map(value).sum::<f32>() / runs as f32
+            }
+        };
+
+        let mut corridor_lengths: BTreeMap<usize, f32> = BTreeMap::new();
+        for report in reports {
+            for (length, count) in &report.corridor_lengths {
+                *corridor_lengths.entry(*length).or_insert(0.0) += *count as f32 / runs as f32;
+            }
+        }
+
+        AveragedMazeReport {
+            runs,
+            cell_count: mean(|report| report.cell_count as f32),
+            dead_ends: mean(|report| report.dead_ends as f32),
+            three_way_junctions: mean(|report| report.three_way_junctions as f32),
+            four_way_junctions: mean(|report| report.four_way_junctions as f32),
+            corridor_lengths,
+            river_factor: mean(|report| report.river_factor),
+            solution_length: mean(|report| report.solution_length as f32),
+            solution_decision_points: mean(|report| report.solution_decision_points as f32),
+            longest_dead_branch: mean(|report| report.longest_dead_branch as f32),
+        }
+    }
+
+    fn build_report(
+        links: &HashMap<Coord, Vec<Coord>>,
+        distance_map: &[Vec<i32>],
+        solution: &[Coord],
+    ) -> MazeReport {
+        let exit_count = |coord: &Coord| links[coord].len();
+
+        let cell_count = links.len();
+        let dead_ends = links.keys().filter(|coord| exit_count(coord) == 1).count();
+        let three_way_junctions = links.keys().filter(|coord| exit_count(coord) == 3).count();
+        let four_way_junctions = links.keys().filter(|coord| exit_count(coord) >= 4).count();
+
+        // Corridors: connected runs of cells with exactly two exits.
+        let mut corridor_lengths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut corridor_cell_count = 0;
+        let mut visited: HashSet<Coord> = HashSet::new();
+        for coord in links.keys() {
+            if exit_count(coord) != 2 || visited.contains(coord) {
+                continue;
+            }
+
+            let mut length = 0;
+            let mut work_stack = vec![*coord];
+            visited.insert(*coord);
+
+            while let Some(current_coord) = work_stack.pop() {
+                length += 1;
+
+                for neighbour_coord in &links[&current_coord] {
+                    if exit_count(neighbour_coord) == 2 && visited.insert(*neighbour_coord) {
+                        work_stack.push(*neighbour_coord);
+                    }
+                }
+            }
+
+            *corridor_lengths.entry(length).or_insert(0) += 1;
+            corridor_cell_count += length;
+        }
+
+        let finish = solution[solution.len() - 1];
+        let solution_decision_points = solution[..solution.len() - 1]
+            .iter()
+            .filter(|coord| exit_count(coord) >= 3)
+            .count();
+
+        // Breadth first search outwards from every solution cell at once.
+        let mut branch_depths: HashMap<Coord, usize> =
+            solution.iter().map(|coord| (*coord, 0)).collect();
+        let mut work_queue: VecDeque<Coord> = solution.iter().copied().collect();
+        let mut longest_dead_branch = 0;
+        while let Some(current_coord) = work_queue.pop_front() {
+            let depth = branch_depths[&current_coord];
+            longest_dead_branch = longest_dead_branch.max(depth);
+
+            for neighbour_coord in &links[&current_coord] {
+                if !branch_depths.contains_key(neighbour_coord) {
+                    branch_depths.insert(*neighbour_coord, depth + 1);
+                    work_queue.push_back(*neighbour_coord);
+                }
+            }
+        }
+
+        MazeReport {
+            cell_count,
+            dead_ends,
+            three_way_junctions,
+            four_way_junctions,
+            corridor_lengths,
+            river_factor: if cell_count == 0 {
+                0.0
+            } else {
+                corridor_cell_count as f32 / cell_count as f32
+            },
+            solution_length: distance_map[finish.y][finish.x] as usize,
+            solution_decision_points,
+            longest_dead_branch,
+        }
+    }
+}
+
+impl MazeReport {
+    #[allow(unused)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report serialization has failed")
+    }
+
+    #[allow(unused)]
+    pub fn print_table(&self) {
+        print_table(&[
+            ("cells", self.cell_count.to_string()),
+            ("dead ends", self.dead_ends.to_string()),
+            ("3-way junctions", self.three_way_junctions.to_string()),
+            ("4-way junctions", self.four_way_junctions.to_string()),
+            (
+                "corridor lengths",
+                format_distribution(&self.corridor_lengths, |count| count.to_string()),
+            ),
+            ("river factor", format!("{:.3}", self.river_factor)),
+            ("solution length", self.solution_length.to_string()),
+            (
+                "solution decision points",
+                self.solution_decision_points.to_string(),
+            ),
+            ("longest dead branch", self.longest_dead_branch.to_string()),
+        ]);
+    }
+}
+
+impl AveragedMazeReport {
+    #[allow(unused)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report serialization has failed")
+    }
+
+    #[allow(unused)]
+    pub fn print_table(&self) {
+        print_table(&[
+            ("runs", self.runs.to_string()),
+            ("cells", format!("{:.2}", self.cell_count)),
+            ("dead ends", format!("{:.2}", self.dead_ends)),
+            (
+                "3-way junctions",
+                format!("{:.2}", self.three_way_junctions),
+            ),
+            ("4-way junctions", format!("{:.2}", self.four_way_junctions)),
+            (
+                "corridor lengths",
+                format_distribution(&self.corridor_lengths, |count| format!("{:.2}", count)),
+            ),
+            ("river factor", format!("{:.3}", self.river_factor)),
+            ("solution length", format!("{:.2}", self.solution_length)),
+            (
+                "solution decision points",
+                format!("{:.2}", self.solution_decision_points),
+            ),
+            (
+                "longest dead branch",
+                format!("{:.2}", self.longest_dead_branch),
+            ),
+        ]);
+    }
+}
+
+fn print_table(rows: &[(&str, String)]) {
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+    for (label, value) in rows {
+        println!("{:<label_width$} | {}", label, value);
+    }
+}
+
+fn format_distribution<T>(
+    distribution: &BTreeMap<usize, T>,
+    format: impl Fn(&T) -> String,
+) -> String {
+    distribution
+        .iter()
+        .map(|(length, count)| format!("{}:{}", length, format(count)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
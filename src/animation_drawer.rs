@@ -0,0 +1,621 @@
+use std::collections::HashSet;
+
+use svg::node::element::{path::Data, Animate, Circle, Line, Path, Rectangle};
+use svg::Document;
+use tiny_skia::{FillRule, LineCap, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+use crate::{
+    encoded_file::EncodedFile,
+    error::MazeError,
+    maze_builder::CarveTrace,
+    solver::SearchTrace,
+    svg_drawer::SvgImage,
+    theme::{Rgb, Theme},
+    util::{EAST, NORTH, SOUTH, WEST},
+    Maze, Pair,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationOptions {
+    // Frames per second. Browsers slow down GIF frames shorter than 2/100 s, so GIFs look best at
+    // 50 or less.
+    pub frame_rate: f32,
+    // How many frames each carve, search or path step gets. Below 1 several steps share a frame.
+    pub frames_per_step: f32,
+    // Seconds the finished drawing stays up before the animation starts over.
+    pub hold: f32,
+    pub theme: Theme,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            frame_rate: 30.0,
+            frames_per_step: 1.0,
+            hold: 2.0,
+            theme: Theme::print(),
+        }
+    }
+}
+
+pub type GifImage = EncodedFile;
+
+type Segment = ((f32, f32), (f32, f32));
+
+// A wall of the finished maze, or one the builder carved away at step `carved_at`.
+struct Wall {
+    line: Segment,
+    carved_at: Option<usize>,
+}
+
+// Steps of an animation and the frames they land on. Carving comes first, then the search, then
+// the path, one step per carve, expanded cell or path segment.
+struct Timeline<'a> {
+    walls: Vec<Wall>,
+    // Cells the search expanded, with the step each one shows up at.
+    visits: Vec<(usize, Pair<usize>)>,
+    path_segments: Vec<(usize, Segment)>,
+    markers: Vec<((f32, f32), Rgb)>,
+    step_count: usize,
+    width: f32,
+    height: f32,
+    options: &'a AnimationOptions,
+}
+
+impl<'a> Timeline<'a> {
+    fn new(
+        maze: &Maze,
+        carves: &CarveTrace,
+        search: Option<&SearchTrace>,
+        solution: &[Pair<usize>],
+        options: &'a AnimationOptions,
+    ) -> Result<Timeline<'a>, MazeError> {
+        if !(options.frame_rate.is_finite() && options.frame_rate > 0.0) {
+            return Err(MazeError::InvalidOptions("frame rate must be positive"));
+        }
+        if !(options.frames_per_step.is_finite() && options.frames_per_step > 0.0) {
+            return Err(MazeError::InvalidOptions(
+                "frames per step must be positive",
+            ));
+        }
+        if !(options.hold.is_finite() && options.hold >= 0.0) {
+            return Err(MazeError::InvalidOptions("hold cannot be negative"));
+        }
+
+        let theme = &options.theme;
+        let side = |coord: Pair<usize>, dir: usize| -> Segment {
+            let ((x1, y1), (x2, y2)) = Maze::side_segment(coord, dir, theme.cell_size);
+            (
+                (theme.padding + x1, theme.padding + y1),
+                (theme.padding + x2, theme.padding + y2),
+            )
+        };
+
+        // Walls of the finished maze stay up, the carved ones come down at their step. A passage
+        // carved twice comes down the first time.
+        let mut walls: Vec<Wall> = maze
+            .wall_sides()
+            .into_iter()
+            .map(|(coord, dir)| Wall {
+                line: side(coord, dir),
+                carved_at: None,
+            })
+            .collect();
+        let mut carved: HashSet<(Pair<usize>, usize)> = HashSet::new();
+        for (step, carve) in carves.carves.iter().enumerate() {
+            // Both sides of a passage count as the same one, the north or east side of a cell.
+            let passage = match carve.dir {
+                SOUTH => (Pair::new(carve.coord.x, carve.coord.y + 1), NORTH),
+                WEST => (Pair::new(carve.coord.x - 1, carve.coord.y), EAST),
+                dir => (carve.coord, dir),
+            };
+
+            if carved.insert(passage) {
+                walls.push(Wall {
+                    line: side(passage.0, passage.1),
+                    carved_at: Some(step),
+                });
+            }
+        }
+
+        let center = |coord: &Pair<usize>| {
+            (
+                theme.padding + (coord.x as f32 + 0.5) * theme.cell_size,
+                theme.padding + (coord.y as f32 + 0.5) * theme.cell_size,
+            )
+        };
+
+        let mut step_count = carves.carves.len();
+        let mut visits = vec![];
+        for step in search.map_or(&[][..], |search| &search.steps) {
+            visits.push((step_count, step.expanded));
+            step_count += 1;
+        }
+
+        let mut path_segments = vec![];
+        for pair in solution.windows(2) {
+            path_segments.push((step_count, (center(&pair[0]), center(&pair[1]))));
+            step_count += 1;
+        }
+
+        let markers = [
+            (solution.first(), theme.start_marker),
+            (solution.last(), theme.finish_marker),
+        ]
+        .into_iter()
+        .filter_map(|(coord, color)| Some((center(coord?), color?)))
+        .collect();
+
+        Ok(Timeline {
+            walls,
+            visits,
+            path_segments,
+            markers,
+            step_count,
+            width: theme.cell_size * maze.width as f32 + theme.padding * 2.0,
+            height: theme.cell_size * maze.height as f32 + theme.padding * 2.0,
+            options,
+        })
+    }
+
+    // Frame 0 is the untouched maze, the last one has every step done.
+    fn frame_count(&self) -> usize {
+        (self.step_count as f32 * self.options.frames_per_step).ceil() as usize + 1
+    }
+
+    fn steps_done(&self, frame: usize) -> usize {
+        ((frame as f32 / self.options.frames_per_step).floor() as usize).min(self.step_count)
+    }
+
+    // Seconds into the animation at which `step` shows, which is the start of the first frame
+    // with it done.
+    fn time_of(&self, step: usize) -> f32 {
+        let frame = ((step + 1) as f32 * self.options.frames_per_step).ceil();
+        frame / self.options.frame_rate
+    }
+
+    fn duration(&self) -> f32 {
+        self.frame_count() as f32 / self.options.frame_rate + self.options.hold
+    }
+}
+
+// Animations of a maze being carved, searched and solved. The maze is drawn as it ends up, with
+// the carved walls put back at the start.
+pub struct AnimationDrawer;
+
+impl AnimationDrawer {
+    // Walls disappear in carve order, then the searched cells fill in and the path grows. Uses
+    // SMIL, so it runs on its own wherever the SVG is shown.
+    pub fn render_svg(
+        maze: &Maze,
+        carves: &CarveTrace,
+        search: Option<&SearchTrace>,
+        solution: &[Pair<usize>],
+        options: &AnimationOptions,
+    ) -> Result<SvgImage, MazeError> {
+        let timeline = Timeline::new(maze, carves, search, solution, options)?;
+        let theme = &options.theme;
+        let duration = timeline.duration();
+        // Discrete visibility switch at `time`, starting over with the whole animation.
+        let switch = |from: &str, to: &str, time: f32| {
+            Animate::new()
+                .set("attributeName", "visibility")
+                .set("values", format!("{};{}", from, to))
+                .set("keyTimes", format!("0;{}", time / duration))
+                .set("calcMode", "discrete")
+                .set("dur", format!("{}s", duration))
+                .set("repeatCount", "indefinite")
+        };
+
+        let mut document = Document::new().set("viewBox", (0, 0, timeline.width, timeline.height));
+
+        if let Some(background) = theme.background {
+            document = document.add(
+                Rectangle::new()
+                    .set("width", timeline.width)
+                    .set("height", timeline.height)
+                    .set("fill", svg_color(background)),
+            );
+        }
+
+        if let Some(visit_color) = theme.heat_map.first() {
+            for (step, coord) in &timeline.visits {
+                document = document.add(
+                    Rectangle::new()
+                        .set("x", theme.padding + coord.x as f32 * theme.cell_size)
+                        .set("y", theme.padding + coord.y as f32 * theme.cell_size)
+                        .set("width", theme.cell_size)
+                        .set("height", theme.cell_size)
+                        .set("fill", svg_color(*visit_color))
+                        .set("visibility", "hidden")
+                        .add(switch("hidden", "visible", timeline.time_of(*step))),
+                );
+            }
+        }
+
+        // Walls that stay go into one path, the carved ones each get their own line.
+        let mut data = Data::new();
+        for wall in &timeline.walls {
+            let ((x1, y1), (x2, y2)) = wall.line;
+            match wall.carved_at {
+                None => data = data.move_to((x1, y1)).line_to((x2, y2)),
+                Some(step) => {
+                    document =
+                        document.add(
+                            line(wall.line, theme.wall_color, theme.wall_width, "square")
+                                .add(switch("visible", "hidden", timeline.time_of(step))),
+                        )
+                }
+            }
+        }
+        document = document.add(
+            Path::new()
+                .set("d", data)
+                .set("fill", "none")
+                .set("stroke", svg_color(theme.wall_color))
+                .set("stroke-width", theme.wall_width)
+                .set("stroke-linecap", "square"),
+        );
+
+        for (step, segment) in &timeline.path_segments {
+            document = document.add(
+                line(*segment, theme.path_color, theme.path_width, "round")
+                    .set("visibility", "hidden")
+                    .add(switch("hidden", "visible", timeline.time_of(*step))),
+            );
+        }
+
+        for ((x, y), color) in &timeline.markers {
+            document = document.add(
+                Circle::new()
+                    .set("cx", *x)
+                    .set("cy", *y)
+                    .set("r", theme.cell_size * 0.3)
+                    .set("fill", svg_color(*color)),
+            );
+        }
+
+        Ok(SvgImage::from_document(&document))
+    }
+
+    // Same animation as `render_svg`, one pixel per theme unit. Frames are drawn without
+    // antialiasing so every pixel is one of the theme colours, which keeps GIFs small. GIFs are at
+    // most 65535 pixels wide and high.
+    pub fn render_gif(
+        maze: &Maze,
+        carves: &CarveTrace,
+        search: Option<&SearchTrace>,
+        solution: &[Pair<usize>],
+        options: &AnimationOptions,
+    ) -> Result<GifImage, MazeError> {
+        let timeline = Timeline::new(maze, carves, search, solution, options)?;
+        let theme = &options.theme;
+        let pixel_width = (timeline.width.ceil() as u64).max(1);
+        let pixel_height = (timeline.height.ceil() as u64).max(1);
+        let (width, height) = u16::try_from(pixel_width)
+            .ok()
+            .zip(u16::try_from(pixel_height).ok())
+            .ok_or(MazeError::ImageTooLarge {
+                width: pixel_width,
+                height: pixel_height,
+            })?;
+
+        let background = theme.background.unwrap_or((255, 255, 255));
+        let mut palette = vec![background, theme.wall_color, theme.path_color];
+        palette.extend(theme.heat_map.first());
+        palette.extend(timeline.markers.iter().map(|(_, color)| *color));
+        let palette_data: Vec<u8> = palette.iter().flat_map(|(r, g, b)| [*r, *g, *b]).collect();
+
+        let mut encoder = gif::Encoder::new(vec![], width, height, &palette_data)
+            .map_err(|_| MazeError::EncodingFailed("GIF"))?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|_| MazeError::EncodingFailed("GIF"))?;
+
+        // Frames with the same steps done are merged into one longer frame. GIF delays are in
+        // hundredths of a second, rounded on the running time so they do not drift.
+        let frame_count = timeline.frame_count();
+        let mut previous_pixels: Option<Vec<u8>> = None;
+        let mut frame = 0;
+        while frame < frame_count {
+            let steps_done = timeline.steps_done(frame);
+            let mut next_frame = frame + 1;
+            while next_frame < frame_count && timeline.steps_done(next_frame) == steps_done {
+                next_frame += 1;
+            }
+
+            let centiseconds = |frame: usize| (frame as f32 * 100.0 / options.frame_rate).round();
+            let mut delay = centiseconds(next_frame) - centiseconds(frame);
+            if next_frame == frame_count {
+                delay += (options.hold * 100.0).round();
+            }
+
+            let pixmap =
+                AnimationDrawer::draw_frame(&timeline, steps_done, background, width, height)?;
+            let pixels: Vec<u8> = pixmap
+                .pixels()
+                .iter()
+                .map(|pixel| {
+                    let color = (pixel.red(), pixel.green(), pixel.blue());
+                    palette.iter().position(|c| *c == color).unwrap_or(0) as u8
+                })
+                .collect();
+
+            // Only the part that changed since the last frame is stored, drawn over the rest.
+            let (left, top, right, bottom) = match &previous_pixels {
+                None => (0, 0, width as usize, height as usize),
+                Some(previous_pixels) => {
+                    changed_area(previous_pixels, &pixels, width as usize).unwrap_or((0, 0, 1, 1))
+                }
+            };
+            let area_pixels: Vec<u8> = (top..bottom)
+                .flat_map(|y| &pixels[y * width as usize + left..y * width as usize + right])
+                .copied()
+                .collect();
+
+            let mut gif_frame = gif::Frame::from_indexed_pixels(
+                (right - left) as u16,
+                (bottom - top) as u16,
+                area_pixels,
+                None,
+            );
+            gif_frame.left = left as u16;
+            gif_frame.top = top as u16;
+            gif_frame.dispose = gif::DisposalMethod::Keep;
+            gif_frame.delay = delay.min(u16::MAX as f32) as u16;
+            encoder
+                .write_frame(&gif_frame)
+                .map_err(|_| MazeError::EncodingFailed("GIF"))?;
+
+            previous_pixels = Some(pixels);
+            frame = next_frame;
+        }
+
+        Ok(EncodedFile::new(
+            encoder
+                .into_inner()
+                .map_err(|_| MazeError::EncodingFailed("GIF"))?,
+        ))
+    }
+
+    fn draw_frame(
+        timeline: &Timeline,
+        steps_done: usize,
+        background: Rgb,
+        width: u16,
+        height: u16,
+    ) -> Result<Pixmap, MazeError> {
+        let theme = &timeline.options.theme;
+        let mut pixmap =
+            Pixmap::new(width as u32, height as u32).ok_or(MazeError::ImageTooLarge {
+                width: width as u64,
+                height: height as u64,
+            })?;
+        pixmap.fill(tiny_skia::Color::from_rgba8(
+            background.0,
+            background.1,
+            background.2,
+            255,
+        ));
+
+        if let Some(visit_color) = theme.heat_map.first() {
+            for (_, coord) in timeline
+                .visits
+                .iter()
+                .filter(|(step, _)| *step < steps_done)
+            {
+                let cell = Rect::from_xywh(
+                    theme.padding + coord.x as f32 * theme.cell_size,
+                    theme.padding + coord.y as f32 * theme.cell_size,
+                    theme.cell_size,
+                    theme.cell_size,
+                );
+                if let Some(cell) = cell {
+                    pixmap.fill_rect(cell, &paint(*visit_color), Transform::identity(), None);
+                }
+            }
+        }
+
+        let mut walls = PathBuilder::new();
+        for wall in &timeline.walls {
+            if wall.carved_at.is_none_or(|step| step >= steps_done) {
+                let ((x1, y1), (x2, y2)) = wall.line;
+                walls.move_to(x1, y1);
+                walls.line_to(x2, y2);
+            }
+        }
+        stroke(
+            &mut pixmap,
+            walls,
+            theme.wall_color,
+            theme.wall_width,
+            LineCap::Square,
+        );
+
+        let mut path = PathBuilder::new();
+        for (_, ((x1, y1), (x2, y2))) in timeline
+            .path_segments
+            .iter()
+            .filter(|(step, _)| *step < steps_done)
+        {
+            path.move_to(*x1, *y1);
+            path.line_to(*x2, *y2);
+        }
+        stroke(
+            &mut pixmap,
+            path,
+            theme.path_color,
+            theme.path_width,
+            LineCap::Round,
+        );
+
+        for ((x, y), color) in &timeline.markers {
+            if let Some(circle) = PathBuilder::from_circle(*x, *y, theme.cell_size * 0.3) {
+                pixmap.fill_path(
+                    &circle,
+                    &paint(*color),
+                    FillRule::Winding,
+                    Transform::identity(),
+                    None,
+                );
+            }
+        }
+
+        Ok(pixmap)
+    }
+}
+
+// Smallest rectangle holding every pixel that differs, as left, top, right and bottom with the
+// right and bottom edges exclusive. `None` when nothing changed.
+fn changed_area(
+    previous_pixels: &[u8],
+    pixels: &[u8],
+    width: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let mut area: Option<(usize, usize, usize, usize)> = None;
+
+    for (i, _) in pixels
+        .iter()
+        .zip(previous_pixels)
+        .enumerate()
+        .filter(|(_, (pixel, previous_pixel))| pixel != previous_pixel)
+    {
+        let (x, y) = (i % width, i / width);
+        area = Some(match area {
+            None => (x, y, x + 1, y + 1),
+            Some((left, top, right, bottom)) => {
+                (left.min(x), top.min(y), right.max(x + 1), bottom.max(y + 1))
+            }
+        });
+    }
+
+    area
+}
+
+fn line(((x1, y1), (x2, y2)): Segment, color: Rgb, width: f32, line_cap: &str) -> Line {
+    Line::new()
+        .set("x1", x1)
+        .set("y1", y1)
+        .set("x2", x2)
+        .set("y2", y2)
+        .set("stroke", svg_color(color))
+        .set("stroke-width", width)
+        .set("stroke-linecap", line_cap)
+}
+
+fn svg_color((r, g, b): Rgb) -> String {
+    format!("rgb({},{},{})", r, g, b)
+}
+
+fn paint((r, g, b): Rgb) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = false;
+    paint
+}
+
+// Empty paths draw nothing.
+fn stroke(pixmap: &mut Pixmap, path: PathBuilder, color: Rgb, width: f32, line_cap: LineCap) {
+    if let Some(path) = path.finish() {
+        let stroke = Stroke {
+            width,
+            line_cap,
+            ..Stroke::default()
+        };
+
+        pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{maze_builder::MazeBuilder, solver::Solver};
+
+    // A carved maze with its carve trace, search trace and solution.
+    fn traced_maze() -> (Maze, CarveTrace, SearchTrace, Vec<Pair<usize>>) {
+        let mut maze = Maze::new_full(4, 3);
+        let mut carves = CarveTrace::default();
+        MazeBuilder::backtracker_maze_creation_with_trace(
+            &mut maze,
+            &mut StdRng::seed_from_u64(7),
+            &mut carves,
+        );
+        let (solution, search) = Solver::dijkstra_path_finding_solver_with_trace(
+            &maze,
+            Pair::new(0, 0),
+            Pair::new(3, 2),
+        )
+        .unwrap();
+
+        (maze, carves, search, solution)
+    }
+
+    #[test]
+    fn writes_gif_files() {
+        let (maze, carves, search, solution) = traced_maze();
+        let image = AnimationDrawer::render_gif(
+            &maze,
+            &carves,
+            Some(&search),
+            &solution,
+            &AnimationOptions::default(),
+        )
+        .unwrap();
+
+        assert!(image.as_bytes().starts_with(b"GIF89a"));
+    }
+
+    #[test]
+    fn rejects_timing_that_is_not_positive() {
+        let (maze, carves, search, solution) = traced_maze();
+        let frame_rate = MazeError::InvalidOptions("frame rate must be positive");
+        let frames_per_step = MazeError::InvalidOptions("frames per step must be positive");
+        let cases = [
+            (0.0, 1.0, frame_rate),
+            (-30.0, 1.0, frame_rate),
+            (30.0, 0.0, frames_per_step),
+            (30.0, -1.0, frames_per_step),
+        ];
+
+        for (frame_rate, frames_per_step, error) in cases {
+            let options = AnimationOptions {
+                frame_rate,
+                frames_per_step,
+                ..AnimationOptions::default()
+            };
+
+            assert_eq!(
+                AnimationDrawer::render_svg(&maze, &carves, Some(&search), &solution, &options),
+                Err(error)
+            );
+            assert_eq!(
+                AnimationDrawer::render_gif(&maze, &carves, Some(&search), &solution, &options),
+                Err(error)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_gifs_wider_than_the_format_allows() {
+        let (maze, carves, search, solution) = traced_maze();
+        let options = AnimationOptions {
+            theme: Theme {
+                cell_size: 20_000.0,
+                padding: 0.0,
+                ..Theme::print()
+            },
+            ..AnimationOptions::default()
+        };
+
+        assert_eq!(
+            AnimationDrawer::render_gif(&maze, &carves, Some(&search), &solution, &options),
+            Err(MazeError::ImageTooLarge {
+                width: 80_000,
+                height: 60_000,
+            })
+        );
+    }
+}
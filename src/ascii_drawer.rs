@@ -1,53 +1,301 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
 
+use crate::solver::CheckpointTour;
+use crate::theme::Theme;
 use crate::util::*;
 use crate::{Maze, Pair};
 
+#[derive(Debug, Clone)]
+pub struct AsciiOptions {
+    pub wall: char,
+    pub empty: char,
+    pub solution: char,
+    // Characters per cell, not counting the wall between cells. Marks sit in the middle.
+    pub cell_width: usize,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        AsciiOptions {
+            wall: '█',
+            empty: ' ',
+            solution: 'x',
+            cell_width: 1,
+        }
+    }
+}
+
+// Text has no colours or sizes, so only the theme's characters carry over.
+impl From<&Theme> for AsciiOptions {
+    fn from(theme: &Theme) -> Self {
+        AsciiOptions {
+            wall: theme.wall_char,
+            solution: theme.path_char,
+            ..AsciiOptions::default()
+        }
+    }
+}
+
 #[allow(unused)]
 pub struct AsciiDrawer;
 
 impl AsciiDrawer {
     #[allow(unused)]
     pub fn draw(maze: &Maze, solution: Vec<Pair<usize>>) {
-        let solution_set: HashSet<Pair<usize>> = solution.into_iter().collect();
+        println!(
+            "{}",
+            AsciiDrawer::to_string(maze, &solution, &AsciiOptions::default())
+        );
+    }
+
+    #[allow(unused)]
+    pub fn to_string(maze: &Maze, solution: &[Pair<usize>], options: &AsciiOptions) -> String {
+        let mut output = String::new();
+        AsciiDrawer::render(&mut output, maze, solution, options)
+            .expect("Writing to a String cannot fail");
+
+        output
+    }
+
+    #[allow(unused)]
+    pub fn write(
+        output: &mut impl io::Write,
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        options: &AsciiOptions,
+    ) -> io::Result<()> {
+        output.write_all(AsciiDrawer::to_string(maze, solution, options).as_bytes())
+    }
+
+    #[allow(unused)]
+    pub fn render(
+        output: &mut impl fmt::Write,
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        options: &AsciiOptions,
+    ) -> fmt::Result {
+        let marks: HashMap<Pair<usize>, char> = solution
+            .iter()
+            .map(|coord| (*coord, options.solution))
+            .collect();
+
+        AsciiDrawer::render_marks(output, maze, &marks, options)
+    }
+
+    // Checkpoints are labelled 1-9, then a-z, then `*`, in the order the tour visits them.
+    #[allow(unused)]
+    pub fn draw_checkpoint_tour(maze: &Maze, tour: &CheckpointTour) {
+        println!(
+            "{}",
+            AsciiDrawer::checkpoint_tour_to_string(maze, tour, &AsciiOptions::default())
+        );
+    }
+
+    #[allow(unused)]
+    pub fn checkpoint_tour_to_string(
+        maze: &Maze,
+        tour: &CheckpointTour,
+        options: &AsciiOptions,
+    ) -> String {
+        let mut marks: HashMap<Pair<usize>, char> = tour
+            .path
+            .iter()
+            .map(|coord| (*coord, options.solution))
+            .collect();
+        for (i, checkpoint) in tour.checkpoints.iter().enumerate() {
+            let label = std::char::from_digit(i as u32 + 1, 36).unwrap_or('*');
+            marks.insert(*checkpoint, label);
+        }
+
+        let mut output = String::new();
+        AsciiDrawer::render_marks(&mut output, maze, &marks, options)
+            .expect("Writing to a String cannot fail");
+
+        output
+    }
+
+    // Keys show up as lowercase and doors as uppercase colour letters, on top of `marks`.
+    fn render_marks(
+        output: &mut impl fmt::Write,
+        maze: &Maze,
+        marks: &HashMap<Pair<usize>, char>,
+        options: &AsciiOptions,
+    ) -> fmt::Result {
+        // Nothing to draw, and no bottom row to close.
+        if maze.width == 0 || maze.height == 0 {
+            return Ok(());
+        }
+
+        let mut marks = marks.clone();
+        for key in &maze.keys {
+            marks.insert(key.coord, key.color.letter());
+        }
+
+        let door_letter = |x: usize, y: usize, dir: usize| {
+            maze.doors
+                .iter()
+                .find(|door| door.coord == Pair::new(x, y) && door.dir == dir)
+                .map(|door| door.color.letter().to_ascii_uppercase())
+        };
+
+        let cell_width = options.cell_width.max(1);
+        let mark_offset = (cell_width - 1) / 2;
+        // A cell-wide run of `c`, or of `empty` with `c` in the middle.
+        let gap = |c: char| -> String {
+            (0..cell_width)
+                .map(|i| if i == mark_offset { c } else { options.empty })
+                .collect()
+        };
+        let wall_run: String = std::iter::repeat_n(options.wall, cell_width).collect();
 
         for y in 0..maze.height {
-            print!("█");
+            write!(output, "{}", options.wall)?;
             for x in 0..maze.width {
                 let i = y * maze.width + x;
 
                 if maze.cells[i].paths[NORTH] {
-                    print!("██");
+                    write!(output, "{}{}", wall_run, options.wall)?;
                 } else {
-                    print!(" █");
+                    let door = door_letter(x, y, NORTH).unwrap_or(options.empty);
+                    write!(output, "{}{}", gap(door), options.wall)?;
                 }
             }
-            print!("\n");
+            writeln!(output)?;
 
-            print!("█");
+            if maze.cells[y * maze.width].paths[WEST] {
+                write!(output, "{}", options.wall)?;
+            } else {
+                write!(output, "{}", options.empty)?;
+            }
             for x in 0..maze.width {
                 let i = y * maze.width + x;
 
-                if solution_set.contains(&Pair::new(x, y)) {
-                    print!("x");
-                } else {
-                    print!(" ");
-                }
+                let mark = marks.get(&Pair::new(x, y)).copied();
+                write!(output, "{}", gap(mark.unwrap_or(options.empty)))?;
 
                 if maze.cells[i].paths[EAST] {
-                    print!("█");
+                    write!(output, "{}", options.wall)?;
                 } else {
-                    print!(" ");
+                    let door = door_letter(x, y, EAST).unwrap_or(options.empty);
+                    write!(output, "{}", door)?;
                 }
             }
-            print!("\n")
+            writeln!(output)?;
         }
 
-        print!("█");
-        for _ in 0..maze.width {
-            print!("██");
+        write!(output, "{}", options.wall)?;
+        for x in 0..maze.width {
+            let i = (maze.height - 1) * maze.width + x;
+
+            if maze.cells[i].paths[SOUTH] {
+                write!(output, "{}{}", wall_run, options.wall)?;
+            } else {
+                write!(output, "{}{}", gap(options.empty), options.wall)?;
+            }
         }
+        writeln!(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_door::KeyColor;
+    use crate::opening::OpeningKind;
+
+    // A 3 x 2 maze that winds right along the top row and back left along the bottom one, open on
+    // the west side of both rows.
+    fn winding_maze() -> Maze {
+        let mut maze = Maze::new_full(3, 2);
+        maze.connect_cells(0, 0, EAST);
+        maze.connect_cells(1, 0, EAST);
+        maze.connect_cells(2, 0, SOUTH);
+        maze.connect_cells(1, 1, EAST);
+        maze.connect_cells(0, 1, EAST);
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 0), WEST)
+            .unwrap();
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(0, 1), WEST)
+            .unwrap();
+
+        maze
+    }
+
+    fn winding_solution() -> Vec<Pair<usize>> {
+        [(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1)]
+            .iter()
+            .map(|(x, y)| Pair::new(*x, *y))
+            .collect()
+    }
+
+    #[test]
+    fn draws_walls_openings_and_solution() {
+        let output = AsciiDrawer::to_string(
+            &winding_maze(),
+            &winding_solution(),
+            &AsciiOptions::default(),
+        );
+
+        assert_eq!(
+            output,
+            "███████\n\
+             \x20x x x█\n\
+             █████ █\n\
+             \x20x x x█\n\
+             ███████\n"
+        );
+    }
+
+    #[test]
+    fn draws_wide_cells_with_custom_characters() {
+        let options = AsciiOptions {
+            wall: '#',
+            cell_width: 3,
+            ..AsciiOptions::default()
+        };
+        let output = AsciiDrawer::to_string(&winding_maze(), &winding_solution()[..3], &options);
+
+        assert_eq!(
+            output,
+            "#############\n\
+             \x20 x   x   x #\n\
+             #########   #\n\
+             \x20           #\n\
+             #############\n"
+        );
+    }
+
+    #[test]
+    fn draws_keys_and_doors() {
+        let mut maze = winding_maze();
+        maze.add_door(KeyColor::Red, Pair::new(2, 0), SOUTH)
+            .unwrap();
+        maze.add_key(KeyColor::Red, Pair::new(1, 0)).unwrap();
+
+        let output = AsciiDrawer::to_string(&maze, &[], &AsciiOptions::default());
+
+        assert_eq!(
+            output,
+            "███████\n\
+             \x20  r  █\n\
+             █████R█\n\
+             \x20     █\n\
+             ███████\n"
+        );
+    }
+
+    #[test]
+    fn draws_nothing_for_empty_mazes() {
+        let options = AsciiOptions::default();
 
-        print!("\n\n");
+        assert_eq!(
+            AsciiDrawer::to_string(&Maze::new_full(0, 3), &[], &options),
+            ""
+        );
+        assert_eq!(
+            AsciiDrawer::to_string(&Maze::new_full(3, 0), &[], &options),
+            ""
+        );
     }
 }
@@ -0,0 +1,494 @@
+use crate::ascii_drawer::AsciiOptions;
+use crate::error::{ParseError, ParseErrorKind};
+use crate::key_door::KeyColor;
+use crate::opening::OpeningKind;
+use crate::util::*;
+use crate::{Maze, Pair};
+
+#[allow(unused)]
+#[derive(Debug)]
+pub struct ParsedMaze {
+    pub maze: Maze,
+    // Marked cells in reading order, which is not necessarily the order the path takes.
+    pub solution: Vec<Coord>,
+}
+
+// Characters making up one of the text formats.
+struct Glyphs {
+    corner: char,
+    horizontal_wall: char,
+    vertical_wall: char,
+    empty: char,
+    solution: char,
+    cell_width: usize,
+}
+
+// Reads mazes drawn as text: wall lines and cell lines alternate, starting and ending with a wall
+// line. Gaps in the outer wall become openings, the first one the entrance and the others exits.
+// Lowercase colour letters in cells are keys and uppercase ones in passages are doors, the way
+// `AsciiDrawer` draws them.
+pub struct AsciiParser;
+
+impl AsciiParser {
+    // Picks the format by the first character: `+` for the `+--+` style, anything else for the
+    // default `AsciiDrawer` output.
+    #[allow(unused)]
+    pub fn parse(text: &str) -> Result<ParsedMaze, ParseError> {
+        let (_, lines) = maze_lines(text);
+
+        if lines.first().and_then(|line| line.first()) == Some(&'+') {
+            AsciiParser::parse_box(text)
+        } else {
+            AsciiParser::parse_blocks(text, &AsciiOptions::default())
+        }
+    }
+
+    // Reads what `AsciiDrawer` draws with the same options.
+    #[allow(unused)]
+    pub fn parse_blocks(text: &str, options: &AsciiOptions) -> Result<ParsedMaze, ParseError> {
+        AsciiParser::parse_glyphs(
+            text,
+            &Glyphs {
+                corner: options.wall,
+                horizontal_wall: options.wall,
+                vertical_wall: options.wall,
+                empty: options.empty,
+                solution: options.solution,
+                cell_width: options.cell_width.max(1),
+            },
+        )
+    }
+
+    // `+` corners, `-` and `|` walls and `x` for the solution. Cells are as wide as the gap between
+    // the first two corners.
+    #[allow(unused)]
+    pub fn parse_box(text: &str) -> Result<ParsedMaze, ParseError> {
+        let (line_offset, lines) = maze_lines(text);
+        let top = lines.first().ok_or(ParseError {
+            line: line_offset + 1,
+            column: 1,
+            kind: ParseErrorKind::Empty,
+        })?;
+        let cell_width = top
+            .iter()
+            .skip(1)
+            .position(|c| *c == '+')
+            .filter(|cell_width| *cell_width > 0)
+            .ok_or(ParseError {
+                line: line_offset + 1,
+                column: top.len() + 1,
+                kind: ParseErrorKind::IncompleteCell,
+            })?;
+
+        AsciiParser::parse_glyphs(
+            text,
+            &Glyphs {
+                corner: '+',
+                horizontal_wall: '-',
+                vertical_wall: '|',
+                empty: ' ',
+                solution: 'x',
+                cell_width,
+            },
+        )
+    }
+
+    fn parse_glyphs(text: &str, glyphs: &Glyphs) -> Result<ParsedMaze, ParseError> {
+        let (line_offset, lines) = maze_lines(text);
+        let error = |row: usize, column: usize, kind: ParseErrorKind| ParseError {
+            line: line_offset + row + 1,
+            column: column + 1,
+            kind,
+        };
+
+        let line_length = lines
+            .first()
+            .ok_or(error(0, 0, ParseErrorKind::Empty))?
+            .len();
+        let stride = glyphs.cell_width + 1;
+        if line_length < stride + 1 || (line_length - 1) % stride != 0 {
+            return Err(error(0, line_length, ParseErrorKind::IncompleteCell));
+        }
+        if lines.len() < 3 || lines.len() % 2 == 0 {
+            return Err(error(lines.len(), 0, ParseErrorKind::MissingLine));
+        }
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() > line_length {
+                return Err(error(row, line_length, ParseErrorKind::LineTooLong));
+            }
+        }
+
+        let width = (line_length - 1) / stride;
+        let height = (lines.len() - 1) / 2;
+        let mark_offset = (glyphs.cell_width - 1) / 2;
+
+        // Editors tend to strip trailing spaces, so missing characters read as empty.
+        let at = |row: usize, column: usize| -> char {
+            lines[row].get(column).copied().unwrap_or(glyphs.empty)
+        };
+        let unexpected = |row: usize, column: usize| {
+            error(row, column, ParseErrorKind::UnexpectedChar(at(row, column)))
+        };
+
+        // A cell-wide run starting at `column`: all `wall`, or empty with an optional mark in the
+        // middle. Returns whether it is a wall and the mark.
+        let read_run = |row: usize, column: usize, wall: Option<char>| {
+            if wall.is_some() && (0..glyphs.cell_width).all(|i| Some(at(row, column + i)) == wall) {
+                return Ok((true, None));
+            }
+
+            let mut mark = None;
+            for i in 0..glyphs.cell_width {
+                let c = at(row, column + i);
+                if c == glyphs.empty {
+                    continue;
+                }
+                if i != mark_offset {
+                    return Err(unexpected(row, column + i));
+                }
+                mark = Some(c);
+            }
+
+            Ok((false, mark))
+        };
+        let door_color = |mark: char| {
+            Some(mark)
+                .filter(|mark| mark.is_ascii_uppercase())
+                .and_then(KeyColor::from_letter)
+        };
+        let key_color = |mark: char| {
+            Some(mark)
+                .filter(|mark| mark.is_ascii_lowercase())
+                .and_then(KeyColor::from_letter)
+        };
+
+        let mut maze = Maze::new_full(width, height);
+        let mut solution = vec![];
+        let add_opening = |maze: &mut Maze, coord: Coord, dir: usize, row, column| {
+            let (name, kind) = match maze.openings.len() {
+                0 => ("entrance".to_string(), OpeningKind::Entrance),
+                1 => ("exit".to_string(), OpeningKind::Exit),
+                i => (format!("exit {}", i), OpeningKind::Exit),
+            };
+
+            maze.add_opening(&name, kind, coord, dir)
+                .map_err(|maze_error| error(row, column, ParseErrorKind::InvalidMaze(maze_error)))
+        };
+
+        for y in 0..=height {
+            // Wall line above cell row `y`, or below the last one.
+            let row = y * 2;
+            for x in 0..=width {
+                if at(row, x * stride) != glyphs.corner {
+                    return Err(unexpected(row, x * stride));
+                }
+            }
+
+            for x in 0..width {
+                let column = x * stride + 1;
+                let (is_wall, mark) = read_run(row, column, Some(glyphs.horizontal_wall))?;
+                if is_wall {
+                    continue;
+                }
+
+                if y == 0 || y == height {
+                    if mark.is_some() {
+                        return Err(unexpected(row, column + mark_offset));
+                    }
+
+                    let (coord, dir) = if y == 0 {
+                        (Pair::new(x, 0), NORTH)
+                    } else {
+                        (Pair::new(x, height - 1), SOUTH)
+                    };
+                    add_opening(&mut maze, coord, dir, row, column)?;
+                    continue;
+                }
+
+                maze.connect_cells(x, y, NORTH);
+                if let Some(mark) = mark {
+                    let color = door_color(mark).ok_or(unexpected(row, column + mark_offset))?;
+                    maze.add_door(color, Pair::new(x, y), NORTH)
+                        .map_err(|maze_error| {
+                            error(
+                                row,
+                                column + mark_offset,
+                                ParseErrorKind::InvalidMaze(maze_error),
+                            )
+                        })?;
+                }
+            }
+
+            if y == height {
+                break;
+            }
+
+            // Cell line: the wall left of each cell, then the cell itself.
+            let row = y * 2 + 1;
+            for x in 0..=width {
+                let column = x * stride;
+                let c = at(row, column);
+
+                if c == glyphs.vertical_wall {
+                    // Walls are already up.
+                } else if x == 0 || x == width {
+                    if c != glyphs.empty {
+                        return Err(unexpected(row, column));
+                    }
+
+                    let (coord, dir) = if x == 0 {
+                        (Pair::new(0, y), WEST)
+                    } else {
+                        (Pair::new(width - 1, y), EAST)
+                    };
+                    add_opening(&mut maze, coord, dir, row, column)?;
+                } else if c == glyphs.empty {
+                    maze.connect_cells(x - 1, y, EAST);
+                } else {
+                    let color = door_color(c).ok_or(unexpected(row, column))?;
+                    maze.connect_cells(x - 1, y, EAST);
+                    maze.add_door(color, Pair::new(x - 1, y), EAST)
+                        .map_err(|maze_error| {
+                            error(row, column, ParseErrorKind::InvalidMaze(maze_error))
+                        })?;
+                }
+
+                if x == width {
+                    continue;
+                }
+
+                let column = column + 1;
+                let (_, mark) = read_run(row, column, None)?;
+                let coord = Pair::new(x, y);
+                match mark {
+                    None => {}
+                    Some(mark) if mark == glyphs.solution => solution.push(coord),
+                    Some(mark) => {
+                        let color = key_color(mark).ok_or(unexpected(row, column + mark_offset))?;
+                        maze.add_key(color, coord).map_err(|maze_error| {
+                            error(
+                                row,
+                                column + mark_offset,
+                                ParseErrorKind::InvalidMaze(maze_error),
+                            )
+                        })?;
+                    }
+                }
+            }
+        }
+
+        Ok(ParsedMaze { maze, solution })
+    }
+}
+
+// Lines of the maze as characters, without blank lines around it. Also returns how many lines
+// were skipped at the top, to keep line numbers in errors right.
+fn maze_lines(text: &str) -> (usize, Vec<Vec<char>>) {
+    let lines: Vec<&str> = text.lines().collect();
+    let line_offset = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let line_end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(line_offset, |i| i + 1);
+
+    (
+        line_offset,
+        lines[line_offset..line_end]
+            .iter()
+            .map(|line| line.chars().collect())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ascii_drawer::AsciiDrawer;
+    use crate::maze_builder::MazeBuilder;
+    use crate::solver::Solver;
+
+    // Renders `maze`, parses the text back and checks both mazes and their drawings match.
+    fn assert_round_trip(maze: &Maze, solution: &[Coord], options: &AsciiOptions) {
+        let text = AsciiDrawer::to_string(maze, solution, options);
+        let parsed = AsciiParser::parse_blocks(&text, options).unwrap();
+
+        assert_eq!(
+            (parsed.maze.width, parsed.maze.height),
+            (maze.width, maze.height)
+        );
+        for (parsed_cell, cell) in parsed.maze.cells.iter().zip(&maze.cells) {
+            assert_eq!(parsed_cell.paths, cell.paths);
+        }
+        assert_eq!(parsed.maze.openings, maze.openings);
+        assert_eq!(parsed.maze.keys, maze.keys);
+        assert_eq!(parsed.maze.doors, maze.doors);
+
+        let mut expected_solution = solution.to_vec();
+        expected_solution.sort_by_key(|coord| (coord.y, coord.x));
+        assert_eq!(parsed.solution, expected_solution);
+
+        assert_eq!(
+            AsciiDrawer::to_string(&parsed.maze, &parsed.solution, options),
+            text
+        );
+    }
+
+    // Redraws `AsciiDrawer` output with `+` corners and `-` and `|` walls.
+    fn to_box(text: &str, options: &AsciiOptions) -> String {
+        let stride = options.cell_width + 1;
+
+        text.lines()
+            .enumerate()
+            .map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(column, c)| match c {
+                        c if c != options.wall => c,
+                        _ if row % 2 == 1 => '|',
+                        _ if column % stride == 0 => '+',
+                        _ => '-',
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_a_carved_maze() {
+        let mut maze = Maze::new_full(9, 6);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, 7).unwrap();
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 0), WEST)
+            .unwrap();
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(8, 5), SOUTH)
+            .unwrap();
+        let solution = Solver::opening_path_finding_solver(&maze).unwrap();
+
+        assert_round_trip(&maze, &solution, &AsciiOptions::default());
+    }
+
+    #[test]
+    fn round_trips_keys_and_doors_in_wide_cells() {
+        // Winds right, left and right again through three rows.
+        let mut maze = Maze::new_full(4, 3);
+        for x in 0..3 {
+            maze.connect_cells(x, 0, EAST);
+            maze.connect_cells(x, 1, EAST);
+            maze.connect_cells(x, 2, EAST);
+        }
+        maze.connect_cells(3, 0, SOUTH);
+        maze.connect_cells(0, 1, SOUTH);
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 0), NORTH)
+            .unwrap();
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(3, 2), EAST)
+            .unwrap();
+        // In reading order, which is the order the parser finds them in.
+        maze.add_door(KeyColor::Blue, Pair::new(1, 0), EAST)
+            .unwrap();
+        maze.add_door(KeyColor::Red, Pair::new(0, 1), SOUTH)
+            .unwrap();
+        maze.add_key(KeyColor::Blue, Pair::new(0, 0)).unwrap();
+        maze.add_key(KeyColor::Red, Pair::new(3, 1)).unwrap();
+
+        let options = AsciiOptions {
+            wall: '#',
+            cell_width: 3,
+            ..AsciiOptions::default()
+        };
+        assert_round_trip(&maze, &[], &options);
+    }
+
+    #[test]
+    fn reports_where_an_unexpected_character_is() {
+        let text = "\n\
+                    █████\n\
+                    █ ? █\n\
+                    █████\n";
+
+        let error = AsciiParser::parse(text).unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError {
+                line: 3,
+                column: 3,
+                kind: ParseErrorKind::UnexpectedChar('?'),
+            }
+        );
+    }
+
+    #[test]
+    fn round_trips_the_box_format() {
+        let mut maze = Maze::new_full(7, 5);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 3, 11).unwrap();
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 2), WEST)
+            .unwrap();
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(6, 4), EAST)
+            .unwrap();
+        let solution = Solver::opening_path_finding_solver(&maze).unwrap();
+        let options = AsciiOptions {
+            cell_width: 2,
+            ..AsciiOptions::default()
+        };
+
+        let text = to_box(
+            &AsciiDrawer::to_string(&maze, &solution, &options),
+            &options,
+        );
+        let parsed = AsciiParser::parse(&text).unwrap();
+
+        for (parsed_cell, cell) in parsed.maze.cells.iter().zip(&maze.cells) {
+            assert_eq!(parsed_cell.paths, cell.paths);
+        }
+        assert_eq!(parsed.maze.openings, maze.openings);
+        assert_eq!(parsed.solution.len(), solution.len());
+        assert_eq!(
+            to_box(
+                &AsciiDrawer::to_string(&parsed.maze, &parsed.solution, &options),
+                &options
+            ),
+            text
+        );
+    }
+
+    #[test]
+    fn reports_where_box_lines_go_wrong() {
+        let misaligned_corner = "+--+--+\n\
+                                 |  |  |\n\
+                                 +-+---+\n";
+        let line_too_long = "+--+--+\n\
+                             |     | |\n\
+                             +--+--+\n";
+        let unknown_character = "+--+--+\n\
+                                 |  | ?|\n\
+                                 +--+--+\n";
+
+        assert_eq!(
+            AsciiParser::parse(misaligned_corner).unwrap_err(),
+            ParseError {
+                line: 3,
+                column: 4,
+                kind: ParseErrorKind::UnexpectedChar('-'),
+            }
+        );
+        assert_eq!(
+            AsciiParser::parse(line_too_long).unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 8,
+                kind: ParseErrorKind::LineTooLong,
+            }
+        );
+        assert_eq!(
+            AsciiParser::parse(unknown_character).unwrap_err(),
+            ParseError {
+                line: 2,
+                column: 6,
+                kind: ParseErrorKind::UnexpectedChar('?'),
+            }
+        );
+    }
+}
@@ -1,14 +1,24 @@
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct Cell {
     // North > east > south > west.
     pub paths: [bool; 4],
+    // Cost of stepping into the cell (mud, water, stairs, ...). Must be at least 1, the weighted
+    // solvers return `MazeError::InvalidCost` otherwise.
+    pub cost: u32,
     force_marked_reached: bool,
 }
 
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell::new_empty()
+    }
+}
+
 impl Cell {
     pub fn new_empty() -> Cell {
         Cell {
             paths: [false; 4],
+            cost: 1,
             force_marked_reached: false,
         }
     }
@@ -16,6 +26,7 @@ impl Cell {
     pub fn new_full() -> Cell {
         Cell {
             paths: [true; 4],
+            cost: 1,
             force_marked_reached: false,
         }
     }
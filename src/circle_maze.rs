@@ -3,15 +3,35 @@ use std::collections::HashMap;
 
 use crate::{
     circle_maze_cell::*,
-    util::{CellReachType, Coord},
+    error::MazeError,
+    opening::*,
+    util::{CellReachType, Coord, NORTH},
     Pair,
 };
 
 const STARTER_CELL_SIDES: usize = 6;
 
+// A wall of a `CircleMaze` on the drawing plane. Angles are in degrees, as in `polar_point`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircleWall {
+    // Part of a ring, going round from `from` to `to`.
+    Arc {
+        radius: f32,
+        from: f32,
+        to: f32,
+    },
+    // Straight line going outwards between two rings.
+    Radial {
+        angle: f32,
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+}
+
 pub struct CircleMaze {
     pub height: usize,
     pub cells: Vec<Vec<CircleMazeCell>>,
+    pub openings: Vec<Opening>,
 }
 
 impl CircleMaze {
@@ -60,41 +80,138 @@ impl CircleMaze {
             ]);
         }
 
-        CircleMaze { height, cells }
+        CircleMaze {
+            height,
+            cells,
+            openings: vec![],
+        }
     }
 
-    pub fn cell_at(&self, coord: Pair<usize>) -> &CircleMazeCell {
+    pub fn cell_at(&self, coord: Pair<usize>) -> Result<&CircleMazeCell, MazeError> {
         self.cells
             .get(coord.y)
-            .expect("Missing cell row")
-            .get(coord.x)
-            .expect("Missing cell")
+            .and_then(|row| row.get(coord.x))
+            .ok_or(MazeError::OutOfBounds(coord))
     }
 
-    pub fn cell_at_mut(&mut self, coord: Pair<usize>) -> &mut CircleMazeCell {
+    pub fn cell_at_mut(&mut self, coord: Pair<usize>) -> Result<&mut CircleMazeCell, MazeError> {
         self.cells
             .get_mut(coord.y)
-            .expect("Missing cell row")
-            .get_mut(coord.x)
-            .expect("Missing cell")
+            .and_then(|row| row.get_mut(coord.x))
+            .ok_or(MazeError::OutOfBounds(coord))
     }
 
-    pub fn connect_cells(&mut self, coord: Coord, dir: CircleMazeCellDirection) {
-        self.cell_at_mut(coord).open(dir.clone());
+    // Centre of a cell on the drawing plane, with the maze centre at the origin.
+    pub fn cell_position(&self, coord: Coord, row_height: f32) -> (f32, f32) {
+        let row_length = self.cells[coord.y].len();
+        let r = row_height * coord.y as f32;
+        let alpha = (360.0 / row_length as f32) * (coord.x as f32 + 0.5);
+        let alpha_rad = (alpha / 180.0) * f32::consts::PI;
+        let beta_rad = ((90.0 - alpha) / 180.0) * f32::consts::PI;
 
-        let current_row_len = self.cells[coord.y].len();
+        (r * alpha_rad.sin(), r * beta_rad.sin())
+    }
 
-        let opposite_dir = match dir {
+    // Corners of a cell for heat-map fills: ring edges are straight chords, just inside the arc
+    // walls, and the outer edge is split where the cells of the next ring begin. The centre cell
+    // has no inner edge.
+    pub fn cell_outline(&self, coord: Coord, row_height: f32) -> Vec<(f32, f32)> {
+        let row_length = self.cells[coord.y].len();
+        let outer_split = if coord.y < self.height - 1 {
+            self.cells[coord.y + 1].len() / row_length
+        } else {
+            1
+        };
+        let cell_arc = 360.0 / row_length as f32;
+        let r_inner = (coord.y as f32 - 0.5) * row_height;
+        let r_outer = (coord.y as f32 + 0.5) * row_height;
+
+        let mut outline = vec![];
+
+        if coord.y > 0 {
+            outline.push(polar_point(r_inner, cell_arc * coord.x as f32));
+            outline.push(polar_point(r_inner, cell_arc * (coord.x + 1) as f32));
+        }
+
+        for i in (0..=outer_split).rev() {
+            let alpha = cell_arc * (coord.x as f32 + i as f32 / outer_split as f32);
+            outline.push(polar_point(r_outer, alpha));
+        }
+
+        outline
+    }
+
+    // Every wall, ring walls as arcs and side walls as radial lines.
+    pub fn wall_segments(&self, row_height: f32) -> Vec<CircleWall> {
+        let mut walls = vec![];
+
+        for h in 0..self.height {
+            let r = (h as f32 + 0.5) * row_height;
+            let cell_count = if h == self.height - 1 {
+                self.cells[h].len()
+            } else {
+                self.cells[h + 1].len()
+            };
+
+            for i in 0..cell_count {
+                // "Top" wall.
+                let alpha_from = (360.0 / cell_count as f32) * i as f32;
+                let alpha_to = (360.0 / cell_count as f32) * (i + 1) as f32;
+
+                let is_open = if h == self.height - 1 {
+                    self.cells[h][i].is_open_at(CircleMazeCellDirection::North(0))
+                } else {
+                    self.cells[h + 1][i].is_open_at(CircleMazeCellDirection::South)
+                };
+
+                if !is_open {
+                    walls.push(CircleWall::Arc {
+                        radius: r,
+                        from: alpha_from,
+                        to: alpha_to,
+                    });
+                }
+
+                // "Side" (left) wall.
+                if h < self.height - 1
+                    && !self.cells[h + 1][i].is_open_at(CircleMazeCellDirection::West)
+                {
+                    walls.push(CircleWall::Radial {
+                        angle: alpha_from,
+                        inner_radius: r,
+                        outer_radius: (h as f32 + 1.5) * row_height,
+                    });
+                }
+            }
+        }
+
+        walls
+    }
+
+    // Direction pointing back at `coord` from its neighbour in direction `dir`.
+    pub fn opposite_direction(
+        &self,
+        coord: Coord,
+        dir: CircleMazeCellDirection,
+    ) -> CircleMazeCellDirection {
+        match dir {
             CircleMazeCellDirection::East => CircleMazeCellDirection::West,
             CircleMazeCellDirection::West => CircleMazeCellDirection::East,
             CircleMazeCellDirection::North(_) => CircleMazeCellDirection::South,
             CircleMazeCellDirection::South => {
-                let cell_row_scale_diff = current_row_len / self.cells[coord.y - 1].len();
+                let cell_row_scale_diff = self.cells[coord.y].len() / self.cells[coord.y - 1].len();
                 let north_index = coord.x % cell_row_scale_diff;
 
                 CircleMazeCellDirection::North(north_index)
             }
-        };
+        }
+    }
+
+    pub fn connect_cells(&mut self, coord: Coord, dir: CircleMazeCellDirection) {
+        self.cell_at_mut(coord).expect("Missing cell").open(dir);
+
+        let current_row_len = self.cells[coord.y].len();
+        let opposite_dir = self.opposite_direction(coord, dir);
 
         let opposite_cell = match dir {
             CircleMazeCellDirection::East => self
@@ -130,6 +247,41 @@ impl CircleMaze {
         opposite_cell.open(opposite_dir);
     }
 
+    // Openings can only be cut into the outer ring, on the outer side of a cell.
+    pub fn add_opening(
+        &mut self,
+        name: &str,
+        kind: OpeningKind,
+        coord: Coord,
+    ) -> Result<(), MazeError> {
+        let height = self.height;
+        let cell = self.cell_at_mut(coord)?;
+        let outer_dir = CircleMazeCellDirection::North(0);
+
+        if coord.y != height - 1 || cell.is_open_at(outer_dir) {
+            return Err(MazeError::InvalidOpening(coord));
+        }
+        cell.open(outer_dir);
+
+        self.openings.push(Opening {
+            name: name.to_string(),
+            kind,
+            coord,
+            dir: NORTH,
+        });
+
+        Ok(())
+    }
+
+    #[allow(unused)]
+    pub fn opening(&self, name: &str) -> Option<&Opening> {
+        self.openings.iter().find(|opening| opening.name == name)
+    }
+
+    pub fn opening_endpoints(&self) -> Result<(Coord, Coord), MazeError> {
+        opening_endpoints(&self.openings)
+    }
+
     pub fn neighbours(
         &self,
         coord: Coord,
@@ -138,7 +290,7 @@ impl CircleMaze {
         let mut unfiltered_neighbours = HashMap::new();
         let current_row_len = self.cells[coord.y].len();
 
-        let current_cell = self.cell_at(coord);
+        let current_cell = &self.cells[coord.y][coord.x];
 
         if current_cell.has_default_paths {
             unfiltered_neighbours.insert(
@@ -175,9 +327,144 @@ impl CircleMaze {
             .into_iter()
             .filter(|(_dir, coord)| match reach_type {
                 CellReachType::Anything => true,
-                CellReachType::ReachableOnly => self.cell_at(*coord).reachable(),
-                CellReachType::UnreachableOnly => !self.cell_at(*coord).reachable(),
+                CellReachType::ReachableOnly => self.cells[coord.y][coord.x].reachable(),
+                CellReachType::UnreachableOnly => !self.cells[coord.y][coord.x].reachable(),
             })
             .collect()
     }
 }
+
+// Point at `r` distance from the centre, `alpha` degrees around.
+pub fn polar_point(r: f32, alpha: f32) -> (f32, f32) {
+    let alpha_rad = (alpha / 180.0) * f32::consts::PI;
+    let beta_rad = ((90.0 - alpha) / 180.0) * f32::consts::PI;
+
+    (r * alpha_rad.sin(), r * beta_rad.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROW_HEIGHT: f32 = 10.0;
+
+    // Radius and angle in degrees, in [0, 360), of a point on the drawing plane.
+    fn polar(point: (f32, f32)) -> (f32, f32) {
+        let (x, y) = point;
+        let alpha = x.atan2(y).to_degrees();
+
+        ((x * x + y * y).sqrt(), (alpha + 360.0) % 360.0)
+    }
+
+    fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+            "{actual:?} is not {expected:?}"
+        );
+    }
+
+    fn arc(radius: f32, from: f32, to: f32) -> CircleWall {
+        CircleWall::Arc { radius, from, to }
+    }
+
+    fn radial(angle: f32, inner_radius: f32, outer_radius: f32) -> CircleWall {
+        CircleWall::Radial {
+            angle,
+            inner_radius,
+            outer_radius,
+        }
+    }
+
+    #[test]
+    fn outlines_cells_with_split_outer_edges() {
+        let maze = CircleMaze::new(3);
+        assert_eq!(
+            maze.cells.iter().map(Vec::len).collect::<Vec<_>>(),
+            [1, 6, 12]
+        );
+
+        // The middle ring has two cells outside each of its own.
+        let outline: Vec<_> = maze
+            .cell_outline(Pair::new(1, 1), ROW_HEIGHT)
+            .into_iter()
+            .map(polar)
+            .collect();
+        let expected = [
+            (5.0, 60.0),
+            (5.0, 120.0),
+            (15.0, 120.0),
+            (15.0, 90.0),
+            (15.0, 60.0),
+        ];
+        assert_eq!(outline.len(), expected.len());
+        for (corner, expected) in outline.into_iter().zip(expected) {
+            assert_close(corner, expected);
+        }
+
+        // The outer ring has a single outer edge.
+        let outline: Vec<_> = maze
+            .cell_outline(Pair::new(3, 2), ROW_HEIGHT)
+            .into_iter()
+            .map(polar)
+            .collect();
+        let expected = [(15.0, 90.0), (15.0, 120.0), (25.0, 120.0), (25.0, 90.0)];
+        assert_eq!(outline.len(), expected.len());
+        for (corner, expected) in outline.into_iter().zip(expected) {
+            assert_close(corner, expected);
+        }
+
+        // The centre cell is a hexagon with no inner edge.
+        let outline = maze.cell_outline(Pair::new(0, 0), ROW_HEIGHT);
+        assert_eq!(outline.len(), 7);
+        for corner in outline {
+            assert!((polar(corner).0 - 5.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn lists_the_walls_of_closed_cells() {
+        let walls = CircleMaze::new(3).wall_segments(ROW_HEIGHT);
+
+        // Ring walls follow the cells outside them, the outer wall follows the outer ring.
+        let arcs = |radius: f32, count: usize| {
+            let cell_arc = 360.0 / count as f32;
+            (0..count).map(move |i| arc(radius, cell_arc * i as f32, cell_arc * (i + 1) as f32))
+        };
+        for wall in arcs(5.0, 6).chain(arcs(15.0, 12)).chain(arcs(25.0, 12)) {
+            assert!(walls.contains(&wall), "{wall:?} is missing");
+        }
+        for i in 0..6 {
+            assert!(walls.contains(&radial(60.0 * i as f32, 5.0, 15.0)));
+        }
+        for i in 0..12 {
+            assert!(walls.contains(&radial(30.0 * i as f32, 15.0, 25.0)));
+        }
+        assert_eq!(walls.len(), 6 + 12 + 12 + 6 + 12);
+    }
+
+    #[test]
+    fn leaves_out_open_walls() {
+        let mut maze = CircleMaze::new(3);
+        let closed = maze.wall_segments(ROW_HEIGHT);
+
+        maze.connect_cells(Pair::new(1, 1), CircleMazeCellDirection::North(1));
+        maze.connect_cells(Pair::new(1, 1), CircleMazeCellDirection::West);
+        maze.connect_cells(Pair::new(0, 0), CircleMazeCellDirection::North(4));
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(5, 2))
+            .unwrap();
+
+        let removed: Vec<_> = closed
+            .into_iter()
+            .filter(|wall| !maze.wall_segments(ROW_HEIGHT).contains(wall))
+            .collect();
+        assert_eq!(
+            removed,
+            [
+                radial(60.0, 5.0, 15.0),
+                arc(5.0, 240.0, 300.0),
+                arc(15.0, 90.0, 120.0),
+                arc(25.0, 150.0, 180.0),
+            ]
+        );
+    }
+}
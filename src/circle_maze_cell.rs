@@ -41,7 +41,7 @@ impl CircleMazeCell {
     }
 
     pub fn open(&mut self, dir: CircleMazeCellDirection) {
-        assert!(!self.is_open_at(dir.clone()));
+        assert!(!self.is_open_at(dir));
 
         match dir {
             CircleMazeCellDirection::East => self.paths[0] = false,
@@ -0,0 +1,35 @@
+use std::{fs, io};
+
+// Bytes of a finished PNG, PDF or GIF, whichever binary drawer made them. `SvgImage` is the text
+// counterpart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedFile {
+    data: Vec<u8>,
+}
+
+impl EncodedFile {
+    pub fn new(data: Vec<u8>) -> EncodedFile {
+        EncodedFile { data }
+    }
+
+    #[allow(unused)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[allow(unused)]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    #[allow(unused)]
+    pub fn write_to(&self, output: &mut impl io::Write) -> io::Result<()> {
+        output.write_all(&self.data)
+    }
+
+    // Overwrites whatever is at `path`.
+    #[allow(unused)]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        fs::write(path, &self.data)
+    }
+}
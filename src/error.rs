@@ -0,0 +1,128 @@
+use std::fmt;
+
+use crate::opening::OpeningKind;
+use crate::util::Coord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeError {
+    OutOfBounds(Coord),
+    StartOutOfBounds(Coord),
+    FinishOutOfBounds(Coord),
+    NoPath { start: Coord, finish: Coord },
+    // A reached cell has no open neighbour one step closer to the start, e.g. because its walls
+    // do not match its neighbours' walls.
+    InconsistentWalls(Coord),
+    // Openings can only be cut into the outer wall, once per side.
+    InvalidOpening(Coord),
+    // Walls between cells can only be edited towards an existing neighbour.
+    InvalidWall(Coord),
+    // Doors can only be put into an open passage between two cells, once per passage.
+    InvalidDoor(Coord),
+    MissingOpening(OpeningKind),
+    // Cell costs start at 1, a free cell would let a weighted path run in circles.
+    InvalidCost(Coord),
+    // The total cost of reaching the cell does not fit into an `i32`.
+    CostOverflow(Coord),
+    // Options a builder or drawer cannot work with, such as empty tiles or a DPI that is not
+    // positive.
+    InvalidOptions(&'static str),
+    // The drawing needs more pixels than the image format or the renderer can hold.
+    ImageTooLarge { width: u64, height: u64 },
+    // The encoder of the named format gave up.
+    EncodingFailed(&'static str),
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::OutOfBounds(coord) => {
+                write!(f, "cell ({}, {}) is out of bounds", coord.x, coord.y)
+            }
+            MazeError::StartOutOfBounds(coord) => {
+                write!(f, "start ({}, {}) is out of bounds", coord.x, coord.y)
+            }
+            MazeError::FinishOutOfBounds(coord) => {
+                write!(f, "finish ({}, {}) is out of bounds", coord.x, coord.y)
+            }
+            MazeError::NoPath { start, finish } => write!(
+                f,
+                "no path from ({}, {}) to ({}, {})",
+                start.x, start.y, finish.x, finish.y
+            ),
+            MazeError::InconsistentWalls(coord) => write!(
+                f,
+                "inconsistent walls around cell ({}, {})",
+                coord.x, coord.y
+            ),
+            MazeError::InvalidOpening(coord) => write!(
+                f,
+                "cell ({}, {}) cannot have an opening on that side",
+                coord.x, coord.y
+            ),
+            MazeError::InvalidWall(coord) => write!(
+                f,
+                "cell ({}, {}) has no neighbour on that side",
+                coord.x, coord.y
+            ),
+            MazeError::InvalidDoor(coord) => write!(
+                f,
+                "cell ({}, {}) cannot have a door on that side",
+                coord.x, coord.y
+            ),
+            MazeError::MissingOpening(kind) => write!(f, "maze has no {:?} opening", kind),
+            MazeError::InvalidCost(coord) => {
+                write!(f, "cell ({}, {}) has a cost of 0", coord.x, coord.y)
+            }
+            MazeError::CostOverflow(coord) => write!(
+                f,
+                "cost of reaching cell ({}, {}) is too large",
+                coord.x, coord.y
+            ),
+            MazeError::InvalidOptions(reason) => write!(f, "invalid options: {}", reason),
+            MazeError::ImageTooLarge { width, height } => {
+                write!(f, "a {} x {} pixel image is too large", width, height)
+            }
+            MazeError::EncodingFailed(format) => write!(f, "{} encoding has failed", format),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    UnexpectedChar(char),
+    // The top wall has to cover a whole number of cells.
+    IncompleteCell,
+    // Lines cannot be longer than the top wall.
+    LineTooLong,
+    // Wall lines and cell lines have to alternate, starting and ending with a wall line.
+    MissingLine,
+    InvalidMaze(MazeError),
+}
+
+// Lines and columns count from 1, columns in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        match self.kind {
+            ParseErrorKind::Empty => write!(f, "no maze found"),
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c),
+            ParseErrorKind::IncompleteCell => write!(f, "top wall ends halfway through a cell"),
+            ParseErrorKind::LineTooLong => write!(f, "line is longer than the top wall"),
+            ParseErrorKind::MissingLine => write!(f, "maze ends without a closing wall line"),
+            ParseErrorKind::InvalidMaze(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
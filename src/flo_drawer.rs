@@ -1,21 +1,43 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
 use flo_draw::{
     canvas::{Color, GraphicsContext, GraphicsPrimitives, LineCap},
-    create_drawing_window, with_2d_graphics,
+    create_drawing_window, create_drawing_window_with_events, with_2d_graphics, DrawEvent, Key,
 };
+use futures::executor;
+use futures::StreamExt;
 
-use crate::{circle_maze::CircleMaze, util::Coord, Maze, Pair};
+use crate::{
+    circle_maze::{polar_point, CircleMaze, CircleWall},
+    glyph::number_strokes,
+    solver::{CheckpointTour, SearchTrace},
+    theme::{Rgb, Theme},
+    util::Coord,
+    Maze, Pair,
+};
 
 pub struct FloDrawer;
 
-const CELL_SIZE: f32 = 16.0;
-const LINE_WIDTH: f32 = 6.0;
-const MAZE_PADDING: f32 = 8.0;
-const LINE_MAP: [[f32; 4]; 4] = [
-    [0f32, 0f32, 1f32, 0f32],
-    [1f32, 0f32, 1f32, 1f32],
-    [0f32, 1f32, 1f32, 1f32],
-    [0f32, 0f32, 0f32, 1f32],
-];
+// Playback state of `animate_search`, changed from the window's event thread.
+struct AnimationControls {
+    playing: bool,
+    pending_steps: usize,
+    steps_per_frame: usize,
+    restart: bool,
+    closed: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SearchCellState {
+    Unreached,
+    Frontier,
+    Expanded,
+}
+
+const FRAME_DURATION: Duration = Duration::from_millis(33);
+const MAX_STEPS_PER_FRAME: usize = 4096;
 
 impl FloDrawer {
     #[allow(unused)]
@@ -24,97 +46,208 @@ impl FloDrawer {
         solution: Vec<Pair<usize>>,
         max_distance: i32,
         distance_map: Vec<Vec<i32>>,
+        theme: Theme,
     ) {
         with_2d_graphics(move || {
             let canvas = create_drawing_window("Mazey");
 
             canvas.draw(|gc| {
-                let w: f32 = CELL_SIZE * maze.width as f32;
-                let h: f32 = CELL_SIZE * maze.height as f32;
-
-                gc.clear_canvas(Color::Rgba(0.1, 0.1, 0.1, 1.0));
-                gc.canvas_height(h + (MAZE_PADDING * 2.0));
-                gc.center_region(0.0, -MAZE_PADDING, w, h + MAZE_PADDING);
-
-                for y in 0..maze.height {
-                    for x in 0..maze.width {
-                        let distance_intensity =
-                            (max_distance - distance_map[y][x]) as f32 / max_distance as f32;
-                        gc.fill_color(Color::Rgba(
-                            distance_intensity / 5.0,
-                            distance_intensity / 1.2,
-                            distance_intensity / 5.0,
-                            1.0,
-                        ));
+                FloDrawer::set_up_canvas(gc, &maze, &theme);
+
+                for (y, row) in distance_map.iter().enumerate() {
+                    for (x, distance) in row.iter().enumerate() {
+                        let color = match theme.heat_color(max_distance, *distance) {
+                            Some(color) => color,
+                            None => continue,
+                        };
+
+                        gc.fill_color(flo_color(color));
                         gc.rect(
-                            x as f32 * CELL_SIZE,
-                            y as f32 * CELL_SIZE,
-                            (x + 1) as f32 * CELL_SIZE,
-                            (y + 1) as f32 * CELL_SIZE,
+                            x as f32 * theme.cell_size,
+                            y as f32 * theme.cell_size,
+                            (x + 1) as f32 * theme.cell_size,
+                            (y + 1) as f32 * theme.cell_size,
                         );
                         gc.fill();
                     }
                 }
 
-                gc.stroke_color(Color::Rgba(0.5, 0.6, 0.7, 1.0));
-                gc.line_width(LINE_WIDTH);
-                gc.line_cap(LineCap::Round);
+                FloDrawer::draw_walls(gc, &maze, &theme);
+                FloDrawer::draw_solution(gc, &solution, &theme);
+            });
+        });
+    }
 
-                // Border
-                gc.rect(0.0, 0.0, w, h);
-                gc.stroke();
+    // Draws the tour with its checkpoints numbered in the order they are visited.
+    #[allow(unused)]
+    pub fn draw_checkpoint_tour(maze: Maze, tour: CheckpointTour, theme: Theme) {
+        with_2d_graphics(move || {
+            let canvas = create_drawing_window("Mazey");
 
-                // Walls.
-                for y in 0..maze.height {
-                    for x in 0..maze.width {
-                        let i = y * maze.width + x;
+            canvas.draw(|gc| {
+                FloDrawer::set_up_canvas(gc, &maze, &theme);
 
-                        let start_x: f32 = x as f32 * CELL_SIZE;
-                        let start_y: f32 = y as f32 * CELL_SIZE;
+                FloDrawer::draw_walls(gc, &maze, &theme);
+                FloDrawer::draw_solution(gc, &tour.path, &theme);
 
-                        for dir in 0..2 {
-                            if !maze.cells[i].paths[dir] {
-                                continue;
-                            }
+                let cell_size = theme.cell_size;
+                for (i, checkpoint) in tour.checkpoints.iter().enumerate() {
+                    let center_x = (checkpoint.x as f32 + 0.5) * cell_size;
+                    let center_y = (checkpoint.y as f32 + 0.5) * cell_size;
+
+                    // Labels are inverted: wall-coloured discs with background-coloured digits.
+                    gc.fill_color(flo_color(theme.wall_color));
+                    gc.new_path();
+                    gc.circle(center_x, center_y, cell_size * 0.45);
+                    gc.fill();
 
-                            gc.new_path();
-                            gc.move_to(
-                                start_x + (CELL_SIZE * LINE_MAP[dir][0]),
-                                start_y + (CELL_SIZE * LINE_MAP[dir][1]),
-                            );
-                            gc.line_to(
-                                start_x + (CELL_SIZE * LINE_MAP[dir][2]),
-                                start_y + (CELL_SIZE * LINE_MAP[dir][3]),
-                            );
-                            gc.stroke();
+                    gc.stroke_color(flo_color(theme.background.unwrap_or((0, 0, 0))));
+                    gc.line_width(1.5);
+                    for (from, to) in number_strokes(i + 1, center_x, center_y, cell_size * 0.45) {
+                        gc.new_path();
+                        gc.move_to(from.0, from.1);
+                        gc.line_to(to.0, to.1);
+                        gc.stroke();
+                    }
+                }
+            });
+        });
+    }
+
+    // Replays a search step by step: expanded cells fill in behind the frontier, and the path is
+    // drawn once the search is over. Space plays and pauses, the right arrow steps while paused,
+    // up and down (or + and -) change the speed and R starts over.
+    #[allow(unused)]
+    pub fn animate_search(
+        maze: Maze,
+        solution: Vec<Pair<usize>>,
+        trace: SearchTrace,
+        theme: Theme,
+    ) {
+        with_2d_graphics(move || {
+            let (canvas, events) = create_drawing_window_with_events("Mazey");
+
+            let controls = Arc::new(Mutex::new(AnimationControls {
+                playing: true,
+                pending_steps: 0,
+                steps_per_frame: 1,
+                restart: false,
+                closed: false,
+            }));
+
+            let event_controls = Arc::clone(&controls);
+            thread::spawn(move || {
+                executor::block_on(async move {
+                    let mut events = events;
+
+                    while let Some(event) = events.next().await {
+                        let mut controls = event_controls.lock().expect("Controls are poisoned");
+
+                        match event {
+                            DrawEvent::KeyDown(_, Some(Key::KeySpace)) => {
+                                controls.playing = !controls.playing;
+                            }
+                            DrawEvent::KeyDown(_, Some(Key::KeyRight)) => {
+                                controls.playing = false;
+                                controls.pending_steps += 1;
+                            }
+                            DrawEvent::KeyDown(_, Some(Key::KeyUp | Key::KeyEquals)) => {
+                                controls.steps_per_frame =
+                                    (controls.steps_per_frame * 2).min(MAX_STEPS_PER_FRAME);
+                            }
+                            DrawEvent::KeyDown(_, Some(Key::KeyDown | Key::KeyMinus)) => {
+                                controls.steps_per_frame = (controls.steps_per_frame / 2).max(1);
+                            }
+                            DrawEvent::KeyDown(_, Some(Key::KeyR)) => {
+                                controls.restart = true;
+                            }
+                            DrawEvent::Closed => break,
+                            _ => {}
                         }
                     }
+
+                    event_controls.lock().expect("Controls are poisoned").closed = true;
+                });
+            });
+
+            let mut cell_states = vec![SearchCellState::Unreached; maze.width * maze.height];
+            let mut step_index = 0;
+            cell_states[trace.start.index(maze.width)] = SearchCellState::Frontier;
+
+            loop {
+                let step_count = {
+                    let mut controls = controls.lock().expect("Controls are poisoned");
+                    if controls.closed {
+                        break;
+                    }
+
+                    if controls.restart {
+                        controls.restart = false;
+                        cell_states.fill(SearchCellState::Unreached);
+                        cell_states[trace.start.index(maze.width)] = SearchCellState::Frontier;
+                        step_index = 0;
+                    }
+
+                    if controls.playing {
+                        controls.steps_per_frame
+                    } else {
+                        std::mem::take(&mut controls.pending_steps)
+                    }
+                };
+
+                for step in trace.steps.iter().skip(step_index).take(step_count) {
+                    cell_states[step.expanded.index(maze.width)] = SearchCellState::Expanded;
+                    for coord in &step.discovered {
+                        cell_states[coord.index(maze.width)] = SearchCellState::Frontier;
+                    }
                 }
+                step_index = (step_index + step_count).min(trace.steps.len());
 
-                // Solution.
-                if !solution.is_empty() {
-                    gc.stroke_color(Color::Rgba(1.0, 0.4, 0.1, 1.0));
-                    for i in 0..solution.len() - 1 {
-                        gc.move_to(
-                            (solution[i].x as f32 + 0.5) * CELL_SIZE,
-                            (solution[i].y as f32 + 0.5) * CELL_SIZE,
-                        );
-                        gc.line_to(
-                            (solution[i + 1].x as f32 + 0.5) * CELL_SIZE,
-                            (solution[i + 1].y as f32 + 0.5) * CELL_SIZE,
+                canvas.draw(|gc| {
+                    FloDrawer::set_up_canvas(gc, &maze, &theme);
+                    let cell_size = theme.cell_size;
+
+                    for (i, cell_state) in cell_states.iter().enumerate() {
+                        let color = match cell_state {
+                            SearchCellState::Unreached => continue,
+                            SearchCellState::Frontier => Color::Rgba(0.95, 0.8, 0.2, 1.0),
+                            SearchCellState::Expanded => Color::Rgba(0.2, 0.35, 0.6, 1.0),
+                        };
+                        let x = (i % maze.width) as f32;
+                        let y = (i / maze.width) as f32;
+
+                        gc.fill_color(color);
+                        gc.rect(
+                            x * cell_size,
+                            y * cell_size,
+                            (x + 1.0) * cell_size,
+                            (y + 1.0) * cell_size,
                         );
-                        gc.stroke();
+                        gc.fill();
                     }
-                }
-            });
+
+                    FloDrawer::draw_walls(gc, &maze, &theme);
+                    if step_index == trace.steps.len() {
+                        FloDrawer::draw_solution(gc, &solution, &theme);
+                    }
+                });
+
+                thread::sleep(FRAME_DURATION);
+            }
         });
     }
 
-    pub fn draw_circle_maze(maze: CircleMaze, solution: Vec<Coord>) {
+    pub fn draw_circle_maze(
+        maze: CircleMaze,
+        solution: Vec<Coord>,
+        max_distance: i32,
+        distance_map: Vec<Vec<i32>>,
+        theme: Theme,
+    ) {
         with_2d_graphics(move || {
             let canvas = create_drawing_window("Mazey");
 
-            let level_height = 40.0f32;
+            let level_height = theme.ring_height();
             let w = level_height * (maze.height as f32 * 2.0 - 1.0);
             let h = w;
 
@@ -122,99 +255,178 @@ impl FloDrawer {
             let offset_y = h / 2.0;
 
             canvas.draw(|gc| {
-                gc.clear_canvas(Color::Rgba(0.1, 0.1, 0.1, 1.0));
-                gc.canvas_height(h + (MAZE_PADDING * 2.0));
-                gc.center_region(0.0, -MAZE_PADDING, w, h + MAZE_PADDING);
+                gc.clear_canvas(flo_color(theme.background.unwrap_or((0, 0, 0))));
+                gc.canvas_height(h + (theme.padding * 2.0));
+                gc.center_region(0.0, -theme.padding, w, h + theme.padding);
 
-                gc.stroke_color(Color::Rgba(0.65, 0.7, 0.75, 1.0));
-                gc.line_width(LINE_WIDTH);
-                gc.line_cap(LineCap::Round);
+                // Heat map.
+                for (y, row) in distance_map.iter().enumerate() {
+                    for (x, distance) in row.iter().enumerate() {
+                        let outline = maze.cell_outline(Pair::new(x, y), level_height);
+                        let color = match theme.heat_color(max_distance, *distance) {
+                            Some(color) => color,
+                            None => continue,
+                        };
 
-                for h in 0..maze.height {
-                    let r = (h as f32 + 0.5) * level_height;
-                    let cell_count = if h == maze.height - 1 {
-                        maze.cells[h].len()
-                    } else {
-                        maze.cells[h + 1].len()
-                    };
-
-                    for i in 0..cell_count {
-                        // "Top" line start.
-                        let alpha_from = (360.0 / cell_count as f32) * i as f32;
-                        let alpha_from_rad = (alpha_from / 180.0) * std::f32::consts::PI;
-                        let beta_from_rad = ((90.0 - alpha_from) / 180.0) * std::f32::consts::PI;
-                        let x_from = r * alpha_from_rad.sin();
-                        let y_from = r * beta_from_rad.sin();
-
-                        // "Top" line finish.
-                        let alpha_to = (360.0 / cell_count as f32) * (i + 1) as f32;
-                        let alpha_to_rad = (alpha_to / 180.0) * std::f32::consts::PI;
-                        let beta_to_rad = ((90.0 - alpha_to) / 180.0) * std::f32::consts::PI;
-                        let x_to = r * alpha_to_rad.sin();
-                        let y_to = r * beta_to_rad.sin();
-
-                        if h == maze.height - 1
-                            || !maze
-                                .cell_at(Pair::new(i, h + 1))
-                                .is_open_at(crate::circle_maze_cell::CircleMazeCellDirection::South)
-                        {
-                            gc.move_to(x_from + offset_x, y_from + offset_y);
-                            gc.line_to(x_to + offset_x, y_to + offset_y);
-                            gc.stroke();
+                        gc.fill_color(flo_color(color));
+                        gc.new_path();
+                        gc.move_to(outline[0].0 + offset_x, outline[0].1 + offset_y);
+                        for (corner_x, corner_y) in outline.iter().skip(1) {
+                            gc.line_to(corner_x + offset_x, corner_y + offset_y);
                         }
+                        gc.close_path();
+                        gc.fill();
+                    }
+                }
+
+                gc.stroke_color(flo_color(theme.wall_color));
+                gc.line_width(theme.wall_width);
+                gc.line_cap(LineCap::Round);
 
-                        // "Side" (left) wall.
-                        if h < maze.height - 1
-                            && !maze
-                                .cell_at(Pair::new(i, h + 1))
-                                .is_open_at(crate::circle_maze_cell::CircleMazeCellDirection::West)
-                        {
-                            // Inner end.
-                            let r_inner = (h as f32 + 1.5) * level_height;
-                            let x_to = r_inner * alpha_from_rad.sin();
-                            let y_to = r_inner * beta_from_rad.sin();
-
-                            // Outer end.
-                            gc.move_to(x_from + offset_x, y_from + offset_y);
-                            gc.line_to(x_to + offset_x, y_to + offset_y);
-                            gc.stroke();
+                for wall in maze.wall_segments(level_height) {
+                    gc.new_path();
+                    match wall {
+                        CircleWall::Arc { radius, from, to } => {
+                            let (x, y) = polar_point(radius, from);
+                            gc.move_to(x + offset_x, y + offset_y);
+                            arc_to(gc, radius, from, to, (offset_x, offset_y));
+                        }
+                        CircleWall::Radial {
+                            angle,
+                            inner_radius,
+                            outer_radius,
+                        } => {
+                            let (x, y) = polar_point(inner_radius, angle);
+                            gc.move_to(x + offset_x, y + offset_y);
+                            let (x, y) = polar_point(outer_radius, angle);
+                            gc.line_to(x + offset_x, y + offset_y);
                         }
                     }
+                    gc.stroke();
                 }
 
                 // Solution.
-                if solution.len() > 1 {
-                    gc.stroke_color(Color::Rgba(1.0, 0.4, 0.1, 1.0));
-                    for i in 1..solution.len() {
-                        let (x_from, y_from) = FloDrawer::circle_maze_pos_for_cell(
-                            &maze,
-                            solution[i - 1],
-                            level_height,
-                        );
-                        let (x_to, y_to) =
-                            FloDrawer::circle_maze_pos_for_cell(&maze, solution[i], level_height);
-                        gc.move_to(x_from + offset_x, y_from + offset_y);
-                        gc.line_to(x_to + offset_x, y_to + offset_y);
-                        gc.stroke();
-                    }
-                }
+                let solution_points: Vec<(f32, f32)> = solution
+                    .iter()
+                    .map(|coord| {
+                        let (x, y) = maze.cell_position(*coord, level_height);
+                        (x + offset_x, y + offset_y)
+                    })
+                    .collect();
+                FloDrawer::draw_path(gc, &solution_points, level_height, &theme);
             });
         });
     }
 
-    fn circle_maze_pos_for_cell(
-        maze: &CircleMaze,
-        cell_coord: Coord,
-        row_height: f32,
-    ) -> (f32, f32) {
-        let row_lenght = maze.cells[cell_coord.y].len();
-        let r = row_height * cell_coord.y as f32;
-        let alpha = (360.0 / row_lenght as f32) * (cell_coord.x as f32 + 0.5);
-        let alpha_rad = (alpha / 180.0) * std::f32::consts::PI;
-        let beta_rad = ((90.0 - alpha) / 180.0) * std::f32::consts::PI;
-        let x = r * alpha_rad.sin();
-        let y = r * beta_rad.sin();
-
-        (x, y)
+    fn set_up_canvas(gc: &mut impl GraphicsPrimitives, maze: &Maze, theme: &Theme) {
+        let w: f32 = theme.cell_size * maze.width as f32;
+        let h: f32 = theme.cell_size * maze.height as f32;
+
+        gc.clear_canvas(flo_color(theme.background.unwrap_or((0, 0, 0))));
+        gc.canvas_height(h + (theme.padding * 2.0));
+        gc.center_region(0.0, -theme.padding, w, h + theme.padding);
+    }
+
+    fn draw_walls(gc: &mut impl GraphicsPrimitives, maze: &Maze, theme: &Theme) {
+        gc.stroke_color(flo_color(theme.wall_color));
+        gc.line_width(theme.wall_width);
+        gc.line_cap(LineCap::Round);
+
+        for (from, to) in maze.wall_segments(theme.cell_size) {
+            gc.new_path();
+            gc.move_to(from.0, from.1);
+            gc.line_to(to.0, to.1);
+            gc.stroke();
+        }
+    }
+
+    fn draw_solution(gc: &mut impl GraphicsPrimitives, solution: &[Pair<usize>], theme: &Theme) {
+        let points: Vec<(f32, f32)> = solution
+            .iter()
+            .map(|coord| {
+                (
+                    (coord.x as f32 + 0.5) * theme.cell_size,
+                    (coord.y as f32 + 0.5) * theme.cell_size,
+                )
+            })
+            .collect();
+
+        FloDrawer::draw_path(gc, &points, theme.cell_size, theme);
+    }
+
+    // Line through `points` with the start and finish markers on its ends, sized for cells
+    // `cell_size` across.
+    fn draw_path(
+        gc: &mut impl GraphicsPrimitives,
+        points: &[(f32, f32)],
+        cell_size: f32,
+        theme: &Theme,
+    ) {
+        if points.len() > 1 {
+            gc.stroke_color(flo_color(theme.path_color));
+            gc.line_width(theme.path_width);
+            gc.line_cap(LineCap::Round);
+            for i in 0..points.len() - 1 {
+                gc.new_path();
+                gc.move_to(points[i].0, points[i].1);
+                gc.line_to(points[i + 1].0, points[i + 1].1);
+                gc.stroke();
+            }
+        }
+
+        let markers = [
+            (points.first(), theme.start_marker),
+            (points.last(), theme.finish_marker),
+        ];
+        for (point, color) in markers {
+            if let (Some((x, y)), Some(color)) = (point, color) {
+                gc.fill_color(flo_color(color));
+                gc.new_path();
+                gc.circle(*x, *y, cell_size * 0.3);
+                gc.fill();
+            }
+        }
+    }
+}
+
+// Arc around the maze centre, which is drawn at `offset`, from the current point at angle
+// `alpha_from` to `alpha_to`, as cubic curves of at most a quarter turn each.
+fn arc_to(
+    gc: &mut impl GraphicsPrimitives,
+    r: f32,
+    alpha_from: f32,
+    alpha_to: f32,
+    (offset_x, offset_y): (f32, f32),
+) {
+    let span = alpha_to - alpha_from;
+    let segments = (span.abs() / 90.0).ceil().max(1.0) as usize;
+    let step = span / segments as f32;
+    // Control points sit along the tangent, which is the radius a quarter turn further on.
+    let handle = 4.0 / 3.0 * (step.to_radians() / 4.0).tan();
+    let tangent = |alpha: f32| {
+        let (x, y) = polar_point(r, alpha + 90.0);
+        (x * handle, y * handle)
+    };
+
+    for i in 0..segments {
+        let from = alpha_from + step * i as f32;
+        let to = from + step;
+        let (from_x, from_y) = polar_point(r, from);
+        let (to_x, to_y) = polar_point(r, to);
+        let (from_dx, from_dy) = tangent(from);
+        let (to_dx, to_dy) = tangent(to);
+
+        gc.bezier_curve_to(
+            to_x + offset_x,
+            to_y + offset_y,
+            from_x + from_dx + offset_x,
+            from_y + from_dy + offset_y,
+            to_x - to_dx + offset_x,
+            to_y - to_dy + offset_y,
+        );
     }
 }
+
+fn flo_color((r, g, b): Rgb) -> Color {
+    Color::Rgba(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0)
+}
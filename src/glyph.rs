@@ -0,0 +1,63 @@
+// Neither drawing backend comes with a font we can rely on, so numbers are drawn as
+// seven-segment digits made of plain lines.
+
+// Segment endpoints in a 1 x 2 box with the origin at the top left: top, upper right, lower
+// right, bottom, lower left, upper left, middle.
+const SEGMENTS: [[f32; 4]; 7] = [
+    [0.0, 0.0, 1.0, 0.0],
+    [1.0, 0.0, 1.0, 1.0],
+    [1.0, 1.0, 1.0, 2.0],
+    [0.0, 2.0, 1.0, 2.0],
+    [0.0, 1.0, 0.0, 2.0],
+    [0.0, 0.0, 0.0, 1.0],
+    [0.0, 1.0, 1.0, 1.0],
+];
+
+// Lit segments per digit, one bit per entry of `SEGMENTS`.
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+
+// Lines spelling out `number`, centered on (`center_x`, `center_y`) and `height` tall.
+pub fn number_strokes(
+    number: usize,
+    center_x: f32,
+    center_y: f32,
+    height: f32,
+) -> Vec<((f32, f32), (f32, f32))> {
+    let mut digits: Vec<usize> = vec![];
+    let mut rest = number;
+    loop {
+        digits.push(rest % 10);
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+
+    let scale = height / 2.0;
+    let advance = scale * 1.6;
+    let width = advance * (digits.len() as f32 - 1.0) + scale;
+    let left = center_x - width / 2.0;
+    let top = center_y - height / 2.0;
+
+    let mut strokes = vec![];
+    for (i, digit) in digits.iter().enumerate() {
+        let digit_left = left + advance * i as f32;
+
+        for (segment, line) in SEGMENTS.iter().enumerate() {
+            if DIGIT_SEGMENTS[*digit] & (1 << segment) == 0 {
+                continue;
+            }
+
+            strokes.push((
+                (digit_left + line[0] * scale, top + line[1] * scale),
+                (digit_left + line[2] * scale, top + line[3] * scale),
+            ));
+        }
+    }
+
+    strokes
+}
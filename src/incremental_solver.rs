@@ -0,0 +1,250 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::error::MazeError;
+use crate::solver::Solver;
+use crate::util::*;
+use crate::Maze;
+
+// Keeps a shortest path tree from `start` over the whole maze and repairs it after every wall
+// edit instead of searching again from scratch. Owns the maze so no edit can slip past it.
+pub struct IncrementalSolver {
+    maze: Maze,
+    start: Coord,
+    finish: Coord,
+    // Same layout as `Solver::build_distance_map`, -1 for unreachable cells.
+    distance_map: Vec<Vec<i32>>,
+    // Neighbour one step closer to the start, `None` for the start and unreachable cells.
+    parent_map: Vec<Vec<Option<Coord>>>,
+}
+
+impl IncrementalSolver {
+    #[allow(unused)]
+    pub fn new(maze: Maze, start: Coord, finish: Coord) -> Result<IncrementalSolver, MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let mut solver = IncrementalSolver {
+            distance_map: vec![vec![-1; maze.width]; maze.height],
+            parent_map: vec![vec![None; maze.width]; maze.height],
+            maze,
+            start,
+            finish,
+        };
+        solver.distance_map[start.y][start.x] = 0;
+        solver.relax_from(start);
+
+        Ok(solver)
+    }
+
+    #[allow(unused)]
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    #[allow(unused)]
+    pub fn into_maze(self) -> Maze {
+        self.maze
+    }
+
+    #[allow(unused)]
+    pub fn distance_map(&self) -> &[Vec<i32>] {
+        &self.distance_map
+    }
+
+    // The tree covers every cell, so moving the finish costs nothing.
+    #[allow(unused)]
+    pub fn set_finish(&mut self, finish: Coord) -> Result<(), MazeError> {
+        self.maze
+            .cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+        self.finish = finish;
+
+        Ok(())
+    }
+
+    #[allow(unused)]
+    pub fn shortest_path(&self) -> Result<Vec<Coord>, MazeError> {
+        if self.distance_map[self.finish.y][self.finish.x] == -1 {
+            return Err(MazeError::NoPath {
+                start: self.start,
+                finish: self.finish,
+            });
+        }
+
+        let mut path = vec![self.finish];
+        let mut current_coord = self.finish;
+        while let Some(parent_coord) = self.parent_map[current_coord.y][current_coord.x] {
+            path.push(parent_coord);
+            current_coord = parent_coord;
+        }
+        path.reverse();
+
+        Ok(path)
+    }
+
+    // Edits the wall through `Maze::set_wall` and updates the distances it affects.
+    #[allow(unused)]
+    pub fn set_wall(&mut self, coord: Coord, dir: usize, is_wall: bool) -> Result<(), MazeError> {
+        let neighbour_coord = self.maze.set_wall(coord, dir, is_wall)?;
+
+        if is_wall {
+            self.close_passage(coord, neighbour_coord);
+        } else {
+            self.open_passage(coord, neighbour_coord);
+        }
+
+        Ok(())
+    }
+
+    // A new passage can only make cells closer. Whichever side is closer to the start may now
+    // offer a shortcut to the other side and everything behind it.
+    fn open_passage(&mut self, coord: Coord, neighbour_coord: Coord) {
+        let distance = self.distance_map[coord.y][coord.x];
+        let neighbour_distance = self.distance_map[neighbour_coord.y][neighbour_coord.x];
+
+        let (near_coord, far_coord) =
+            if neighbour_distance == -1 || (distance != -1 && distance < neighbour_distance) {
+                (coord, neighbour_coord)
+            } else {
+                (neighbour_coord, coord)
+            };
+
+        let near_distance = self.distance_map[near_coord.y][near_coord.x];
+        let far_distance = self.distance_map[far_coord.y][far_coord.x];
+        if near_distance == -1 || (far_distance != -1 && far_distance <= near_distance + 1) {
+            return;
+        }
+
+        self.distance_map[far_coord.y][far_coord.x] = near_distance + 1;
+        self.parent_map[far_coord.y][far_coord.x] = Some(near_coord);
+        self.relax_from(far_coord);
+    }
+
+    // Closing a passage the tree does not use changes nothing. Otherwise the subtree hanging off
+    // it loses its distances and gets them back from its reachable surroundings.
+    fn close_passage(&mut self, coord: Coord, neighbour_coord: Coord) {
+        let subtree_root = if self.parent_map[neighbour_coord.y][neighbour_coord.x] == Some(coord) {
+            neighbour_coord
+        } else if self.parent_map[coord.y][coord.x] == Some(neighbour_coord) {
+            coord
+        } else {
+            return;
+        };
+
+        let mut subtree: Vec<Coord> = vec![subtree_root];
+        let mut i = 0;
+        while i < subtree.len() {
+            let current_coord = subtree[i];
+            for child_coord in Solver::open_neighbours(&self.maze, current_coord) {
+                if self.parent_map[child_coord.y][child_coord.x] == Some(current_coord) {
+                    subtree.push(child_coord);
+                }
+            }
+            i += 1;
+        }
+
+        for coord in &subtree {
+            self.distance_map[coord.y][coord.x] = -1;
+            self.parent_map[coord.y][coord.x] = None;
+        }
+
+        // Every subtree cell next to a cell that kept its distance can be reached through it.
+        // Cells further in are reached later from whichever border cell is closest.
+        let mut open_queue: BinaryHeap<Reverse<(i32, Coord, Coord)>> = BinaryHeap::new();
+        for coord in &subtree {
+            for neighbour_coord in Solver::open_neighbours(&self.maze, *coord) {
+                let neighbour_distance = self.distance_map[neighbour_coord.y][neighbour_coord.x];
+                if neighbour_distance != -1 {
+                    open_queue.push(Reverse((neighbour_distance + 1, *coord, neighbour_coord)));
+                }
+            }
+        }
+
+        while let Some(Reverse((distance, current_coord, parent_coord))) = open_queue.pop() {
+            if self.distance_map[current_coord.y][current_coord.x] != -1 {
+                // Stale queue entry.
+                continue;
+            }
+
+            self.distance_map[current_coord.y][current_coord.x] = distance;
+            self.parent_map[current_coord.y][current_coord.x] = Some(parent_coord);
+
+            for neighbour_coord in Solver::open_neighbours(&self.maze, current_coord) {
+                if self.distance_map[neighbour_coord.y][neighbour_coord.x] == -1 {
+                    open_queue.push(Reverse((distance + 1, neighbour_coord, current_coord)));
+                }
+            }
+        }
+    }
+
+    // Breadth first search outwards from a cell whose distance just dropped, lowering every cell
+    // that can now be reached in fewer steps.
+    fn relax_from(&mut self, coord: Coord) {
+        let mut work_queue: VecDeque<Coord> = VecDeque::from([coord]);
+
+        while let Some(current_coord) = work_queue.pop_front() {
+            let next_distance = self.distance_map[current_coord.y][current_coord.x] + 1;
+
+            for neighbour_coord in Solver::open_neighbours(&self.maze, current_coord) {
+                let neighbour_distance = self.distance_map[neighbour_coord.y][neighbour_coord.x];
+                if neighbour_distance != -1 && neighbour_distance <= next_distance {
+                    continue;
+                }
+
+                self.distance_map[neighbour_coord.y][neighbour_coord.x] = next_distance;
+                self.parent_map[neighbour_coord.y][neighbour_coord.x] = Some(current_coord);
+                work_queue.push_back(neighbour_coord);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze_builder::MazeBuilder;
+    use crate::Pair;
+    use rand::prelude::*;
+
+    #[test]
+    fn repairs_distances_like_a_fresh_search() {
+        let mut rng = StdRng::seed_from_u64(41);
+
+        for seed in 0..4 {
+            let mut maze = Maze::new_full(10, 8);
+            MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, seed).unwrap();
+            let start = Pair::new(rng.gen_range(0..10), rng.gen_range(0..8));
+            let mut solver = IncrementalSolver::new(maze, start, Pair::new(9, 7)).unwrap();
+
+            for _ in 0..300 {
+                let coord = Pair::new(rng.gen_range(0..10), rng.gen_range(0..8));
+                let dir = rng.gen_range(0..4);
+                // Edits towards the outside are rejected and leave everything as it was.
+                let _ = solver.set_wall(coord, dir, rng.gen_bool(0.5));
+
+                let (_, distance_map) = Solver::build_distance_map(solver.maze(), start).unwrap();
+                assert_eq!(solver.distance_map(), distance_map);
+
+                let finish = Pair::new(rng.gen_range(0..10), rng.gen_range(0..8));
+                solver.set_finish(finish).unwrap();
+                match solver.shortest_path() {
+                    Ok(path) => {
+                        assert_eq!(path.len() as i32 - 1, distance_map[finish.y][finish.x]);
+                        for step in path.windows(2) {
+                            assert!(
+                                Solver::open_neighbours(solver.maze(), step[0]).contains(&step[1])
+                            );
+                        }
+                    }
+                    Err(error) => {
+                        assert_eq!(error, MazeError::NoPath { start, finish });
+                        assert_eq!(distance_map[finish.y][finish.x], -1);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::util::Coord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Purple,
+    Orange,
+}
+
+pub const KEY_COLORS: [KeyColor; 6] = [
+    KeyColor::Red,
+    KeyColor::Green,
+    KeyColor::Blue,
+    KeyColor::Yellow,
+    KeyColor::Purple,
+    KeyColor::Orange,
+];
+
+impl KeyColor {
+    // Bit of the colour in a set of held keys.
+    pub fn mask(&self) -> u32 {
+        1 << (*self as u32)
+    }
+
+    // Lowercase for keys, uppercase for doors.
+    pub fn letter(&self) -> char {
+        match self {
+            KeyColor::Red => 'r',
+            KeyColor::Green => 'g',
+            KeyColor::Blue => 'b',
+            KeyColor::Yellow => 'y',
+            KeyColor::Purple => 'p',
+            KeyColor::Orange => 'o',
+        }
+    }
+
+    // Colour of a key letter, either case.
+    pub fn from_letter(letter: char) -> Option<KeyColor> {
+        KEY_COLORS
+            .iter()
+            .find(|color| color.letter() == letter.to_ascii_lowercase())
+            .copied()
+    }
+
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            KeyColor::Red => (220, 40, 40),
+            KeyColor::Green => (40, 170, 60),
+            KeyColor::Blue => (40, 90, 220),
+            KeyColor::Yellow => (230, 190, 20),
+            KeyColor::Purple => (150, 50, 190),
+            KeyColor::Orange => (240, 130, 20),
+        }
+    }
+}
+
+// Locked door in the passage on side `dir` of `coord`. Stored from the side facing north or east,
+// so every passage has a single representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Door {
+    pub color: KeyColor,
+    pub coord: Coord,
+    pub dir: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub color: KeyColor,
+    pub coord: Coord,
+}
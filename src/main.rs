@@ -1,22 +1,43 @@
+mod agent_solver;
+mod analysis;
+mod animation_drawer;
 mod ascii_drawer;
+mod ascii_parser;
 mod cell;
 mod circle_maze;
 mod circle_maze_cell;
+mod encoded_file;
+mod error;
 mod flo_drawer;
+mod glyph;
+mod incremental_solver;
+mod key_door;
 mod maze;
 mod maze_builder;
+mod opening;
 mod pair;
+mod pdf_drawer;
+mod png_drawer;
 mod solver;
 mod svg_drawer;
+mod terminal_drawer;
+mod theme;
 mod util;
+mod validator;
 
+use analysis::*;
+use animation_drawer::{AnimationDrawer, AnimationOptions};
 use circle_maze::CircleMaze;
 use flo_drawer::*;
 use maze::*;
 use maze_builder::*;
+use opening::*;
 use pair::*;
+use pdf_drawer::{PdfDrawer, PdfOptions};
 use solver::*;
 use std::env::args;
+use std::time::{Duration, Instant};
+use theme::Theme;
 
 #[allow(unused)]
 fn maze_example() {
@@ -24,34 +45,240 @@ fn maze_example() {
 
     let width = args
         .get(1)
-        .and_then(|raw_width| usize::from_str_radix(&raw_width, 10).ok())
+        .and_then(|raw_width| raw_width.parse::<usize>().ok())
         .unwrap_or(10);
     let height = args
         .get(2)
-        .and_then(|raw_width| usize::from_str_radix(&raw_width, 10).ok())
+        .and_then(|raw_width| raw_width.parse::<usize>().ok())
         .unwrap_or(10);
 
     let mut maze = Maze::new_full(width, height);
-    let start = Pair::new(0, 0);
-    let finish = Pair::new(width - 1, height - 1);
+    let origin = Pair::new(0, 0);
 
-    // MazeBuilder::random_maze_creation(&mut maze, start);
-    // MazeBuilder::aldous_broder_maze_creation(&mut maze, start);
-    // MazeBuilder::wilson_maze_creation(&mut maze, start);
+    // MazeBuilder::random_maze_creation(&mut maze, origin);
+    // MazeBuilder::aldous_broder_maze_creation(&mut maze, origin);
+    MazeBuilder::wilson_maze_creation(&mut maze, origin).expect("Start is out of bounds");
+    // MazeBuilder::parallel_tiled_maze_creation(&mut maze, 64, 42);
 
-    let solution = Solver::dijkstra_path_finding_solver(&maze, start, finish);
-    let (max_distance, distance_map) = Solver::build_distance_map(&maze, start);
+    let (start, finish) =
+        Solver::longest_boundary_path_endpoints(&maze).expect("Maze has no cells");
+    let start_side = maze.outer_side(start).expect("Start is not on the edge");
+    let finish_side = maze.outer_side(finish).expect("Finish is not on the edge");
+    maze.add_opening("entrance", OpeningKind::Entrance, start, start_side)
+        .expect("Entrance cannot be cut");
+    maze.add_opening("exit", OpeningKind::Exit, finish, finish_side)
+        .expect("Exit cannot be cut");
 
-    FloDrawer::draw(maze, solution, max_distance, distance_map);
+    let solution = Solver::opening_path_finding_solver(&maze).expect("Maze has no solution");
+    let (max_distance, distance_map) =
+        Solver::build_distance_map(&maze, start).expect("Start is out of bounds");
+
+    FloDrawer::draw(maze, solution, max_distance, distance_map, Theme::dark());
 }
 
-fn main() {
+#[allow(unused)]
+fn analysis_example() {
+    let mut reports = vec![];
+
+    for _ in 0..10 {
+        let mut maze = Maze::new_full(20, 20);
+        MazeBuilder::wilson_maze_creation(&mut maze, Pair::new(0, 0))
+            .expect("Start is out of bounds");
+
+        let (start, finish) = Solver::longest_path_endpoints(&maze).expect("Maze has no cells");
+        let report = Analysis::analyse_maze(&maze, start, finish).expect("Maze has no solution");
+
+        report.print_table();
+        println!();
+        reports.push(report);
+    }
+
+    let average = Analysis::average(&reports);
+    average.print_table();
+    println!("{}", average.to_json());
+}
+
+// Compares plain and bidirectional breadth first search between opposite corners, on a seeded
+// maze so runs can be compared. Run as `mazey benchmark [size] [seed] [rounds]`, a 10000 by 10000
+// maze searched 5 times by default.
+fn benchmark_example() {
+    let args: Vec<String> = args().collect();
+
+    let size = args
+        .get(2)
+        .and_then(|raw_size| raw_size.parse::<usize>().ok())
+        .unwrap_or(10_000)
+        .max(1);
+    let seed = args
+        .get(3)
+        .and_then(|raw_seed| raw_seed.parse::<u64>().ok())
+        .unwrap_or(42);
+    let rounds = args
+        .get(4)
+        .and_then(|raw_rounds| raw_rounds.parse::<u32>().ok())
+        .unwrap_or(5)
+        .max(1);
+
+    let mut maze = Maze::new_full(size, size);
+    let started_at = Instant::now();
+    MazeBuilder::parallel_tiled_maze_creation(&mut maze, 64, seed).expect("Tile size is positive");
+    println!("build: {:?}", started_at.elapsed());
+
+    let (start, finish) = (Pair::new(0, 0), Pair::new(size - 1, size - 1));
+
+    // Fastest and average of `rounds` runs.
+    let time = |solve: &dyn Fn() -> usize| {
+        let mut timings = vec![];
+        let mut path_length = 0;
+        for _ in 0..rounds {
+            let started_at = Instant::now();
+            path_length = solve();
+            timings.push(started_at.elapsed());
+        }
+
+        let fastest = *timings.iter().min().expect("No rounds");
+        let average = timings.iter().sum::<Duration>() / rounds;
+        (fastest, average, path_length)
+    };
+
+    let (plain_fastest, plain_average, plain_length) = time(&|| {
+        Solver::dijkstra_path_finding_solver(&maze, start, finish)
+            .expect("Maze has no solution")
+            .len()
+    });
+    println!(
+        "breadth first search: fastest {:?}, average {:?}, path length {}",
+        plain_fastest, plain_average, plain_length
+    );
+
+    let expanded_count = Solver::bidirectional_path_finding_solver(&maze, start, finish)
+        .expect("Maze has no solution")
+        .1;
+    let (fastest, average, length) = time(&|| {
+        Solver::bidirectional_path_finding_solver(&maze, start, finish)
+            .expect("Maze has no solution")
+            .0
+            .len()
+    });
+    println!(
+        "bidirectional breadth first search: fastest {:?}, average {:?}, path length {}, \
+         expanded {} of {} cells",
+        fastest,
+        average,
+        length,
+        expanded_count,
+        size * size
+    );
+
+    println!(
+        "speed-up: {:.2}x fastest, {:.2}x average",
+        plain_fastest.as_secs_f64() / fastest.as_secs_f64(),
+        plain_average.as_secs_f64() / average.as_secs_f64()
+    );
+}
+
+// Writes a puzzle book with an answer key to mazey.pdf. Takes the maze width and height, then
+// one seed per maze.
+#[allow(unused)]
+fn puzzle_book_example() {
+    let args: Vec<String> = args().collect();
+
+    let width = args
+        .get(1)
+        .and_then(|raw_width| raw_width.parse::<usize>().ok())
+        .unwrap_or(20);
+    let height = args
+        .get(2)
+        .and_then(|raw_height| raw_height.parse::<usize>().ok())
+        .unwrap_or(30);
+    let mut seeds: Vec<u64> = args
+        .iter()
+        .skip(3)
+        .filter_map(|raw_seed| raw_seed.parse::<u64>().ok())
+        .collect();
+    if seeds.is_empty() {
+        seeds = (1..=4).collect();
+    }
+
+    let options = PdfOptions {
+        columns: 2,
+        rows: 2,
+        ..PdfOptions::default()
+    };
+    PdfDrawer::render_puzzle_book(&seeds, width, height, &options)
+        .expect("Puzzle book cannot be made")
+        .save("./mazey.pdf")
+        .expect("PDF write has failed");
+}
+
+// Writes the carving, search and solution of a maze as mazey_animation.svg and .gif. Takes the
+// maze width and height.
+#[allow(unused)]
+fn animation_example() {
+    let args: Vec<String> = args().collect();
+
+    let width = args
+        .get(1)
+        .and_then(|raw_width| raw_width.parse::<usize>().ok())
+        .unwrap_or(20);
+    let height = args
+        .get(2)
+        .and_then(|raw_height| raw_height.parse::<usize>().ok())
+        .unwrap_or(20);
+
+    let mut maze = Maze::new_full(width, height);
+    let mut carves = CarveTrace::default();
+    MazeBuilder::wilson_maze_creation_with_trace(&mut maze, Pair::new(0, 0), &mut carves)
+        .expect("Start is out of bounds");
+
+    let (start, finish) =
+        Solver::longest_boundary_path_endpoints(&maze).expect("Maze has no cells");
+    let (solution, search) = Solver::dijkstra_path_finding_solver_with_trace(&maze, start, finish)
+        .expect("Maze has no solution");
+
+    let options = AnimationOptions {
+        frames_per_step: 0.5,
+        ..AnimationOptions::default()
+    };
+    AnimationDrawer::render_svg(&maze, &carves, Some(&search), &solution, &options)
+        .expect("Animation cannot be made")
+        .save("./mazey_animation.svg")
+        .expect("Image write has failed");
+    AnimationDrawer::render_gif(&maze, &carves, Some(&search), &solution, &options)
+        .expect("Animation cannot be made")
+        .save("./mazey_animation.gif")
+        .expect("Image write has failed");
+}
+
+fn circle_maze_example() {
     let mut circle_maze = CircleMaze::new(32);
-    let start = Pair::new(0, 0);
-    let finish = Pair::new(0, 31);
-    MazeBuilder::random_circle_maze_creation(&mut circle_maze, start);
-    let solution =
-        Solver::dijkstra_path_finding_solver_for_circle_maze(&circle_maze, start, finish);
+    MazeBuilder::random_circle_maze_creation(&mut circle_maze, Pair::new(0, 0));
+    let (start, finish) = Solver::longest_boundary_path_endpoints_for_circle_maze(&circle_maze)
+        .expect("Maze has no cells");
+    circle_maze
+        .add_opening("entrance", OpeningKind::Entrance, start)
+        .expect("Entrance cannot be cut");
+    circle_maze
+        .add_opening("exit", OpeningKind::Exit, finish)
+        .expect("Exit cannot be cut");
+    let solution = Solver::opening_path_finding_solver_for_circle_maze(&circle_maze)
+        .expect("Maze has no solution");
+    let (max_distance, distance_map) =
+        Solver::build_distance_map_for_circle_maze(&circle_maze, start)
+            .expect("Start is out of bounds");
     // dbg!(solution);
-    FloDrawer::draw_circle_maze(circle_maze, solution);
+    FloDrawer::draw_circle_maze(
+        circle_maze,
+        solution,
+        max_distance,
+        distance_map,
+        Theme::dark(),
+    );
+}
+
+fn main() {
+    match args().nth(1).as_deref() {
+        Some("benchmark") => benchmark_example(),
+        _ => circle_maze_example(),
+    }
 }
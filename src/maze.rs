@@ -1,4 +1,7 @@
 use crate::cell::*;
+use crate::error::MazeError;
+use crate::key_door::*;
+use crate::opening::*;
 use crate::pair::*;
 use crate::util::*;
 use std::collections::HashMap;
@@ -8,6 +11,12 @@ pub struct Maze {
     pub width: usize,
     pub height: usize,
     pub cells: Vec<Cell>,
+    pub openings: Vec<Opening>,
+    pub doors: Vec<Door>,
+    pub keys: Vec<Key>,
+    // Extra cost of crossing a passage, on top of the cost of the cell entered. Keyed by the cell
+    // south or west of the passage and its north or east side.
+    passage_costs: HashMap<(Pair<usize>, usize), u32>,
 }
 
 impl Maze {
@@ -20,6 +29,10 @@ impl Maze {
             width,
             height,
             cells,
+            openings: vec![],
+            doors: vec![],
+            keys: vec![],
+            passage_costs: HashMap::new(),
         }
     }
 
@@ -32,25 +45,33 @@ impl Maze {
             width,
             height,
             cells,
+            openings: vec![],
+            doors: vec![],
+            keys: vec![],
+            passage_costs: HashMap::new(),
         }
     }
 
-    pub fn cell_at(&self, coord: Pair<usize>) -> &Cell {
-        self.cells
-            .get(coord.index(self.width))
-            .expect("Cell not found")
+    pub fn cell_at(&self, coord: Pair<usize>) -> Result<&Cell, MazeError> {
+        if coord.x >= self.width || coord.y >= self.height {
+            return Err(MazeError::OutOfBounds(coord));
+        }
+
+        Ok(&self.cells[coord.index(self.width)])
     }
 
-    pub fn cell_at_mut(&mut self, coord: Pair<usize>) -> &mut Cell {
-        self.cells
-            .get_mut(coord.index(self.width))
-            .expect("Cell not found")
+    pub fn cell_at_mut(&mut self, coord: Pair<usize>) -> Result<&mut Cell, MazeError> {
+        if coord.x >= self.width || coord.y >= self.height {
+            return Err(MazeError::OutOfBounds(coord));
+        }
+
+        Ok(&mut self.cells[coord.index(self.width)])
     }
 
+    // Cells on the edge are never connected to the outside, that needs an opening (`add_opening`).
     pub fn connect_cells(&mut self, x: usize, y: usize, dir: usize) {
         let i = y * self.width + x;
 
-        self.cells[i].paths[dir] = false;
         let opposite_cell_x: i32 = x as i32 + NEIGHBOUR_MAP[dir][0];
         let opposite_cell_y: i32 = y as i32 + NEIGHBOUR_MAP[dir][1];
 
@@ -59,11 +80,233 @@ impl Maze {
             && opposite_cell_x < self.width as i32
             && opposite_cell_y < self.height as i32
         {
+            self.cells[i].paths[dir] = false;
+
             let opposite_i = opposite_cell_y * self.width as i32 + opposite_cell_x;
             self.cells[opposite_i as usize].paths[(dir + 2) % 4] = false;
         }
     }
 
+    // Puts up or takes down the wall between `coord` and its neighbour in direction `dir`, on both
+    // sides. Returns the neighbour. The outer wall is left to `add_opening`.
+    #[allow(unused)]
+    pub fn set_wall(
+        &mut self,
+        coord: Pair<usize>,
+        dir: usize,
+        is_wall: bool,
+    ) -> Result<Pair<usize>, MazeError> {
+        self.cell_at(coord)?;
+        let neighbour_coord = *self
+            .neighbours(coord, CellReachType::Anything)
+            .get(&dir)
+            .ok_or(MazeError::InvalidWall(coord))?;
+
+        self.cells[coord.index(self.width)].paths[dir] = is_wall;
+        self.cells[neighbour_coord.index(self.width)].paths[(dir + 2) % 4] = is_wall;
+
+        Ok(neighbour_coord)
+    }
+
+    // Every wall once, as a cell and the side it is on. South and west walls are the north and east
+    // walls of the neighbours, so those sides only come up along the edge.
+    pub fn wall_sides(&self) -> Vec<(Pair<usize>, usize)> {
+        let mut sides = vec![];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = &self.cells[y * self.width + x];
+
+                for dir in [NORTH, EAST, SOUTH, WEST] {
+                    if (dir == SOUTH && y < self.height - 1) || (dir == WEST && x > 0) {
+                        continue;
+                    }
+
+                    if cell.paths[dir] {
+                        sides.push((Pair::new(x, y), dir));
+                    }
+                }
+            }
+        }
+
+        sides
+    }
+
+    // Every wall as a line, with `cell_size` square cells and the top left corner of the maze at
+    // the origin.
+    pub fn wall_segments(&self, cell_size: f32) -> Vec<((f32, f32), (f32, f32))> {
+        self.wall_sides()
+            .into_iter()
+            .map(|(coord, dir)| Maze::side_segment(coord, dir, cell_size))
+            .collect()
+    }
+
+    // Side `dir` of the cell at `coord`, laid out like `wall_segments`.
+    pub fn side_segment(
+        coord: Pair<usize>,
+        dir: usize,
+        cell_size: f32,
+    ) -> ((f32, f32), (f32, f32)) {
+        let left = coord.x as f32 * cell_size;
+        let top = coord.y as f32 * cell_size;
+        let (right, bottom) = (left + cell_size, top + cell_size);
+
+        match dir {
+            NORTH => ((left, top), (right, top)),
+            EAST => ((right, top), (right, bottom)),
+            SOUTH => ((left, bottom), (right, bottom)),
+            _ => ((left, top), (left, bottom)),
+        }
+    }
+
+    // First side of the cell that faces the outside of the maze, if any.
+    pub fn outer_side(&self, coord: Pair<usize>) -> Option<usize> {
+        (0..4).find(|dir| match *dir {
+            NORTH => coord.y == 0,
+            EAST => coord.x == self.width - 1,
+            SOUTH => coord.y == self.height - 1,
+            _ => coord.x == 0,
+        })
+    }
+
+    pub fn add_opening(
+        &mut self,
+        name: &str,
+        kind: OpeningKind,
+        coord: Pair<usize>,
+        dir: usize,
+    ) -> Result<(), MazeError> {
+        let faces_outside = match dir {
+            NORTH => coord.y == 0,
+            EAST => coord.x == self.width - 1,
+            SOUTH => coord.y == self.height - 1,
+            WEST => coord.x == 0,
+            _ => false,
+        };
+
+        let cell = self.cell_at_mut(coord)?;
+        if !faces_outside || !cell.paths[dir] {
+            return Err(MazeError::InvalidOpening(coord));
+        }
+        cell.paths[dir] = false;
+
+        self.openings.push(Opening {
+            name: name.to_string(),
+            kind,
+            coord,
+            dir,
+        });
+
+        Ok(())
+    }
+
+    #[allow(unused)]
+    pub fn opening(&self, name: &str) -> Option<&Opening> {
+        self.openings.iter().find(|opening| opening.name == name)
+    }
+
+    pub fn opening_endpoints(&self) -> Result<(Pair<usize>, Pair<usize>), MazeError> {
+        opening_endpoints(&self.openings)
+    }
+
+    // Doors go into open passages between two cells, one door per passage.
+    #[allow(unused)]
+    pub fn add_door(
+        &mut self,
+        color: KeyColor,
+        coord: Pair<usize>,
+        dir: usize,
+    ) -> Result<(), MazeError> {
+        let neighbour_coord = *self
+            .neighbours(coord, CellReachType::Anything)
+            .get(&dir)
+            .ok_or(MazeError::InvalidDoor(coord))?;
+        if self.cell_at(coord)?.paths[dir] || self.door_between(coord, neighbour_coord).is_some() {
+            return Err(MazeError::InvalidDoor(coord));
+        }
+
+        let door = if dir == SOUTH || dir == WEST {
+            Door {
+                color,
+                coord: neighbour_coord,
+                dir: (dir + 2) % 4,
+            }
+        } else {
+            Door { color, coord, dir }
+        };
+        self.doors.push(door);
+
+        Ok(())
+    }
+
+    #[allow(unused)]
+    pub fn add_key(&mut self, color: KeyColor, coord: Pair<usize>) -> Result<(), MazeError> {
+        self.cell_at(coord)?;
+        self.keys.push(Key { color, coord });
+
+        Ok(())
+    }
+
+    // Sets what crossing the passage between `coord` and its neighbour in direction `dir` adds to
+    // the cost of the cell entered, both ways (bridges, ladders, ...). Returns the neighbour.
+    #[allow(unused)]
+    pub fn set_passage_cost(
+        &mut self,
+        coord: Pair<usize>,
+        dir: usize,
+        cost: u32,
+    ) -> Result<Pair<usize>, MazeError> {
+        self.cell_at(coord)?;
+        let neighbour_coord = *self
+            .neighbours(coord, CellReachType::Anything)
+            .get(&dir)
+            .ok_or(MazeError::InvalidWall(coord))?;
+
+        let key = if dir == SOUTH || dir == WEST {
+            (neighbour_coord, (dir + 2) % 4)
+        } else {
+            (coord, dir)
+        };
+        if cost == 0 {
+            self.passage_costs.remove(&key);
+        } else {
+            self.passage_costs.insert(key, cost);
+        }
+
+        Ok(neighbour_coord)
+    }
+
+    // Extra cost of the passage on side `dir` of `coord`, 0 unless set.
+    pub fn passage_cost(&self, coord: Pair<usize>, dir: usize) -> u32 {
+        if self.passage_costs.is_empty() {
+            return 0;
+        }
+
+        let key = if dir == SOUTH || dir == WEST {
+            let neighbour_coord = Pair::new(
+                (coord.x as i32 + NEIGHBOUR_MAP[dir][0]) as usize,
+                (coord.y as i32 + NEIGHBOUR_MAP[dir][1]) as usize,
+            );
+            (neighbour_coord, (dir + 2) % 4)
+        } else {
+            (coord, dir)
+        };
+
+        self.passage_costs.get(&key).copied().unwrap_or(0)
+    }
+
+    pub fn door_between(&self, coord: Pair<usize>, other_coord: Pair<usize>) -> Option<&Door> {
+        self.doors.iter().find(|door| {
+            let door_neighbour_coord = Pair::new(
+                (door.coord.x as i32 + NEIGHBOUR_MAP[door.dir][0]) as usize,
+                (door.coord.y as i32 + NEIGHBOUR_MAP[door.dir][1]) as usize,
+            );
+
+            (door.coord == coord && door_neighbour_coord == other_coord)
+                || (door.coord == other_coord && door_neighbour_coord == coord)
+        })
+    }
+
     pub fn neighbours(
         &self,
         coord: Pair<usize>,
@@ -71,11 +314,9 @@ impl Maze {
     ) -> HashMap<usize, Pair<usize>> {
         let mut neighbour_coords: HashMap<usize, Pair<usize>> = HashMap::new();
         let mut neighbour_dirs: Vec<usize> = vec![];
-        for dir in 0..4 {
-            let raw_neighbour_coord = Pair::new(
-                coord.x as i32 + NEIGHBOUR_MAP[dir][0],
-                coord.y as i32 + NEIGHBOUR_MAP[dir][1],
-            );
+        for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
+            let raw_neighbour_coord =
+                Pair::new(coord.x as i32 + offset[0], coord.y as i32 + offset[1]);
             if raw_neighbour_coord.x < 0
                 || raw_neighbour_coord.y < 0
                 || raw_neighbour_coord.x >= self.width as i32
@@ -1,18 +1,72 @@
 use crate::circle_maze::CircleMaze;
+use crate::error::MazeError;
+use crate::key_door::KEY_COLORS;
+use crate::solver::Solver;
 use crate::util::*;
 use crate::Maze;
 use crate::Pair;
 use rand::prelude::*;
 use rand::seq::SliceRandom;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::thread;
+
+// One passage opened by a builder, towards side `dir` of the cell at `coord`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Carve {
+    pub coord: Coord,
+    pub dir: usize,
+}
+
+// Passages in the order a builder opened them. The builders without a trace carve through one
+// that records nothing, so large mazes do not pay for the list. Circle mazes are not traced, the
+// animations only draw square mazes.
+#[derive(Debug, Clone)]
+pub struct CarveTrace {
+    pub carves: Vec<Carve>,
+    recording: bool,
+}
+
+impl Default for CarveTrace {
+    fn default() -> Self {
+        CarveTrace {
+            carves: vec![],
+            recording: true,
+        }
+    }
+}
+
+impl CarveTrace {
+    fn ignored() -> CarveTrace {
+        CarveTrace {
+            carves: vec![],
+            recording: false,
+        }
+    }
+
+    fn carve(&mut self, maze: &mut Maze, x: usize, y: usize, dir: usize) {
+        maze.connect_cells(x, y, dir);
+        if self.recording {
+            self.carves.push(Carve {
+                coord: Pair::new(x, y),
+                dir,
+            });
+        }
+    }
+}
 
 pub struct MazeBuilder;
 
 impl MazeBuilder {
     #[allow(unused)]
     pub fn binary_tree_maze_creation(maze: &mut Maze) {
+        MazeBuilder::binary_tree_maze_creation_with_trace(maze, &mut CarveTrace::ignored());
+    }
+
+    #[allow(unused)]
+    pub fn binary_tree_maze_creation_with_trace(maze: &mut Maze, trace: &mut CarveTrace) {
         let mut rng = rand::thread_rng();
 
         for y in 0..maze.height {
@@ -28,7 +82,7 @@ impl MazeBuilder {
                         rng.gen_range(0..=1)
                     };
 
-                    maze.connect_cells(x, y, dir);
+                    trace.carve(maze, x, y, dir);
                 }
             }
         }
@@ -36,6 +90,11 @@ impl MazeBuilder {
 
     #[allow(unused)]
     pub fn sidewinder_maze_creation(maze: &mut Maze) {
+        MazeBuilder::sidewinder_maze_creation_with_trace(maze, &mut CarveTrace::ignored());
+    }
+
+    #[allow(unused)]
+    pub fn sidewinder_maze_creation_with_trace(maze: &mut Maze, trace: &mut CarveTrace) {
         let mut rng = rand::thread_rng();
         let mut run_length: usize;
 
@@ -52,21 +111,21 @@ impl MazeBuilder {
                         // Check length of run.
                         // Pick on randomly and erast north.
                         let run_rand_i = rng.gen_range(0..=run_length);
-                        maze.connect_cells(x - run_rand_i, y, NORTH);
+                        trace.carve(maze, x - run_rand_i, y, NORTH);
 
                         run_length = 0;
                     } else if y == 0 {
-                        maze.connect_cells(x, y, EAST);
+                        trace.carve(maze, x, y, EAST);
                     } else {
                         if rng.gen_range(0..=1) == 0 {
                             // Check length of run.
                             // Pick on randomly and erast north.
                             let run_rand_i = rng.gen_range(0..=run_length);
-                            maze.connect_cells(x - run_rand_i, y, NORTH);
+                            trace.carve(maze, x - run_rand_i, y, NORTH);
 
                             run_length = 0;
                         } else {
-                            maze.connect_cells(x, y, EAST);
+                            trace.carve(maze, x, y, EAST);
                             run_length += 1;
                         }
                     }
@@ -77,6 +136,11 @@ impl MazeBuilder {
 
     #[allow(unused)]
     pub fn random_maze_creation(maze: &mut Maze, start: Coord) {
+        MazeBuilder::random_maze_creation_with_trace(maze, start, &mut CarveTrace::ignored());
+    }
+
+    #[allow(unused)]
+    pub fn random_maze_creation_with_trace(maze: &mut Maze, start: Coord, trace: &mut CarveTrace) {
         let mut unreachable_cells: HashSet<Coord> = MazeBuilder::generate_unreachable_cells(maze);
 
         let mut rnd = thread_rng();
@@ -96,9 +160,9 @@ impl MazeBuilder {
 
                 neighbour_dirs.shuffle(&mut rnd);
                 for i in 0..used_neighbour_count {
-                    maze.connect_cells(current_coord.x, current_coord.y, *neighbour_dirs[i]);
-                    work_queue.push_back(neighbour_coords[&neighbour_dirs[i]]);
-                    unreachable_cells.remove(&neighbour_coords[&neighbour_dirs[i]]);
+                    trace.carve(maze, current_coord.x, current_coord.y, *neighbour_dirs[i]);
+                    work_queue.push_back(neighbour_coords[neighbour_dirs[i]]);
+                    unreachable_cells.remove(&neighbour_coords[neighbour_dirs[i]]);
                 }
             }
 
@@ -114,7 +178,8 @@ impl MazeBuilder {
 
                 if !neighbour_coords.is_empty() {
                     let random_reachable_neighbour_dir = neighbour_coords.keys().next().unwrap();
-                    maze.connect_cells(
+                    trace.carve(
+                        maze,
                         unreachable_cell.x,
                         unreachable_cell.y,
                         *random_reachable_neighbour_dir,
@@ -134,6 +199,19 @@ impl MazeBuilder {
 
     #[allow(unused)]
     pub fn aldous_broder_maze_creation(maze: &mut Maze, start: Coord) {
+        MazeBuilder::aldous_broder_maze_creation_with_trace(
+            maze,
+            start,
+            &mut CarveTrace::ignored(),
+        );
+    }
+
+    #[allow(unused)]
+    pub fn aldous_broder_maze_creation_with_trace(
+        maze: &mut Maze,
+        start: Coord,
+        trace: &mut CarveTrace,
+    ) {
         let mut unreachable_cells: HashSet<Coord> = MazeBuilder::generate_unreachable_cells(maze);
 
         unreachable_cells.remove(&start);
@@ -146,7 +224,7 @@ impl MazeBuilder {
             let random_neighbour = neighbours[&random_dir];
 
             if !maze.cells[random_neighbour.index(maze.width)].reachable() {
-                maze.connect_cells(current_cell.x, current_cell.y, random_dir);
+                trace.carve(maze, current_cell.x, current_cell.y, random_dir);
             }
 
             current_cell = random_neighbour;
@@ -155,10 +233,21 @@ impl MazeBuilder {
     }
 
     #[allow(unused)]
-    pub fn wilson_maze_creation(maze: &mut Maze, start: Coord) {
+    pub fn wilson_maze_creation(maze: &mut Maze, start: Coord) -> Result<(), MazeError> {
+        MazeBuilder::wilson_maze_creation_with_trace(maze, start, &mut CarveTrace::ignored())
+    }
+
+    #[allow(unused)]
+    pub fn wilson_maze_creation_with_trace(
+        maze: &mut Maze,
+        start: Coord,
+        trace: &mut CarveTrace,
+    ) -> Result<(), MazeError> {
         let mut unreachable_cells = MazeBuilder::generate_unreachable_cells(maze);
 
-        maze.cell_at_mut(start).mark_reached();
+        maze.cell_at_mut(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?
+            .mark_reached();
 
         unreachable_cells.remove(&start);
 
@@ -194,8 +283,7 @@ impl MazeBuilder {
                 path.push(random_neighbour);
                 origin_dirs.push(*random_neighbour_dir);
 
-                let random_neighbour_cell = maze.cell_at(random_neighbour);
-                if random_neighbour_cell.reachable() {
+                if maze.cells[random_neighbour.index(maze.width)].reachable() {
                     break;
                 }
             }
@@ -205,12 +293,14 @@ impl MazeBuilder {
                 let coord_from = path.pop().unwrap();
                 let dir_to = (origin_dirs.pop().unwrap() + 2) % 4;
 
-                maze.connect_cells(coord_from.x, coord_from.y, dir_to);
+                trace.carve(maze, coord_from.x, coord_from.y, dir_to);
 
                 unreachable_cells.remove(&coord_from);
             }
             unreachable_cells.remove(&path.pop().unwrap());
         }
+
+        Ok(())
     }
 
     #[allow(unused)]
@@ -236,8 +326,8 @@ impl MazeBuilder {
                 neighbour_dirs.shuffle(&mut rnd);
                 for i in 0..used_neighbour_count {
                     maze.connect_cells(current_coord, *neighbour_dirs[i]);
-                    work_queue.push_back(neighbour_coords[&neighbour_dirs[i]]);
-                    unreachable_cells.remove(&neighbour_coords[&neighbour_dirs[i]]);
+                    work_queue.push_back(neighbour_coords[neighbour_dirs[i]]);
+                    unreachable_cells.remove(&neighbour_coords[neighbour_dirs[i]]);
                 }
             }
 
@@ -267,6 +357,281 @@ impl MazeBuilder {
         }
     }
 
+    // Splits the maze into `tile_size` square tiles and carves each tile on a worker thread, then
+    // joins the tiles through a spanning tree over the tile grid. Every tile is a perfect maze and
+    // every tile-tree edge opens exactly one passage, so the whole maze stays perfect.
+    // The outcome only depends on `seed`, never on thread scheduling.
+    #[allow(unused)]
+    pub fn parallel_tiled_maze_creation(
+        maze: &mut Maze,
+        tile_size: usize,
+        seed: u64,
+    ) -> Result<(), MazeError> {
+        MazeBuilder::parallel_tiled_maze_creation_with_trace(
+            maze,
+            tile_size,
+            seed,
+            &mut CarveTrace::ignored(),
+        )
+    }
+
+    // Tiles show up in the trace one after the other, then the passages stitching them.
+    #[allow(unused)]
+    pub fn parallel_tiled_maze_creation_with_trace(
+        maze: &mut Maze,
+        tile_size: usize,
+        seed: u64,
+        trace: &mut CarveTrace,
+    ) -> Result<(), MazeError> {
+        let worker_count = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+
+        MazeBuilder::tiled_maze_creation(maze, tile_size, seed, worker_count, trace)
+    }
+
+    // Tiles are dealt out round robin to at most `worker_count` threads.
+    fn tiled_maze_creation(
+        maze: &mut Maze,
+        tile_size: usize,
+        seed: u64,
+        worker_count: usize,
+        trace: &mut CarveTrace,
+    ) -> Result<(), MazeError> {
+        if tile_size == 0 {
+            return Err(MazeError::InvalidOptions("tile size must be positive"));
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let tile_cols = maze.width.div_ceil(tile_size);
+        let tile_rows = maze.height.div_ceil(tile_size);
+        let tile_count = tile_cols * tile_rows;
+        let (maze_width, maze_height) = (maze.width, maze.height);
+
+        let tile_dimensions = |tile_i: usize| -> (usize, usize) {
+            let tile_x = tile_i % tile_cols;
+            let tile_y = tile_i / tile_cols;
+            (
+                min(tile_size, maze_width - tile_x * tile_size),
+                min(tile_size, maze_height - tile_y * tile_size),
+            )
+        };
+
+        // Seeds are drawn upfront so each tile gets the same one regardless of which worker runs it.
+        let tile_seeds: Vec<u64> = (0..tile_count).map(|_| rng.gen()).collect();
+        let worker_count = worker_count.clamp(1, tile_count.max(1));
+
+        let recording = trace.recording;
+
+        let mut tiles: Vec<Option<(Maze, CarveTrace)>> = vec![];
+        tiles.resize_with(tile_count, || None);
+
+        thread::scope(|scope| {
+            let workers = (0..worker_count)
+                .map(|worker_i| {
+                    let tile_seeds = &tile_seeds;
+                    let tile_dimensions = &tile_dimensions;
+
+                    scope.spawn(move || {
+                        (worker_i..tile_count)
+                            .step_by(worker_count)
+                            .map(|tile_i| {
+                                let (tile_width, tile_height) = tile_dimensions(tile_i);
+                                let mut tile = Maze::new_full(tile_width, tile_height);
+                                let mut tile_rng = StdRng::seed_from_u64(tile_seeds[tile_i]);
+                                let mut tile_trace = CarveTrace {
+                                    carves: vec![],
+                                    recording,
+                                };
+                                MazeBuilder::backtracker_maze_creation_with_trace(
+                                    &mut tile,
+                                    &mut tile_rng,
+                                    &mut tile_trace,
+                                );
+
+                                (tile_i, (tile, tile_trace))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for worker in workers {
+                for (tile_i, tile) in worker.join().expect("Tile worker has failed") {
+                    tiles[tile_i] = Some(tile);
+                }
+            }
+        });
+
+        // Copy tiles into place.
+        for (tile_i, tile) in tiles.into_iter().enumerate() {
+            let (tile, tile_trace) = tile.expect("Missing tile");
+            let offset_x = (tile_i % tile_cols) * tile_size;
+            let offset_y = (tile_i / tile_cols) * tile_size;
+
+            trace
+                .carves
+                .extend(tile_trace.carves.into_iter().map(|carve| Carve {
+                    coord: Pair::new(carve.coord.x + offset_x, carve.coord.y + offset_y),
+                    dir: carve.dir,
+                }));
+
+            for y in 0..tile.height {
+                for x in 0..tile.width {
+                    maze.cells[(offset_y + y) * maze.width + offset_x + x].paths =
+                        tile.cells[y * tile.width + x].paths;
+                }
+            }
+        }
+
+        // Stitch tiles. The tile grid itself is carved as a maze: each of its passages becomes one
+        // opening at a random spot of the shared tile border.
+        let mut tile_grid = Maze::new_full(tile_cols, tile_rows);
+        MazeBuilder::backtracker_maze_creation(&mut tile_grid, &mut rng);
+
+        for tile_y in 0..tile_rows {
+            for tile_x in 0..tile_cols {
+                let tile_cell = &tile_grid.cells[tile_y * tile_cols + tile_x];
+                let (tile_width, tile_height) = tile_dimensions(tile_y * tile_cols + tile_x);
+
+                if tile_y > 0 && !tile_cell.paths[NORTH] {
+                    let x = tile_x * tile_size + rng.gen_range(0..tile_width);
+                    trace.carve(maze, x, tile_y * tile_size, NORTH);
+                }
+
+                if tile_x < tile_cols - 1 && !tile_cell.paths[EAST] {
+                    let y = tile_y * tile_size + rng.gen_range(0..tile_height);
+                    trace.carve(maze, tile_x * tile_size + tile_width - 1, y, EAST);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Puts locked doors on the passages of the solution and each matching key at the far end of a
+    // side branch between the previous door and its own, so the level can always be finished but
+    // only by detouring for every key. Expects an already carved perfect maze. Returns how many
+    // doors were placed, which can be fewer than asked for when there are not enough side
+    // branches or colours. The same maze and `rng` state always give the same puzzle.
+    #[allow(unused)]
+    pub fn key_door_puzzle_creation(
+        maze: &mut Maze,
+        start: Coord,
+        finish: Coord,
+        door_count: usize,
+        rng: &mut impl Rng,
+    ) -> Result<usize, MazeError> {
+        let solution = Solver::dijkstra_path_finding_solver(maze, start, finish)?;
+
+        // Breadth first search away from the solution, recording for every other cell how deep
+        // into its branch it lies and which solution cell the branch hangs off.
+        let mut branches: HashMap<Coord, (usize, usize)> = solution
+            .iter()
+            .enumerate()
+            .map(|(i, coord)| (*coord, (i, 0)))
+            .collect();
+        let mut work_queue: VecDeque<Coord> = solution.iter().copied().collect();
+        while let Some(current_coord) = work_queue.pop_front() {
+            let (anchor_index, depth) = branches[&current_coord];
+            let current_cell = maze.cell_at(current_coord)?;
+
+            for (dir, neighbour_coord) in maze.neighbours(current_coord, CellReachType::Anything) {
+                if !current_cell.paths[dir] && !branches.contains_key(&neighbour_coord) {
+                    branches.insert(neighbour_coord, (anchor_index, depth + 1));
+                    work_queue.push_back(neighbour_coord);
+                }
+            }
+        }
+
+        // Deepest branch cell per solution cell. The finish has no passage after it to lock.
+        let mut key_spots: HashMap<usize, (usize, Coord)> = HashMap::new();
+        for (coord, (anchor_index, depth)) in &branches {
+            if *depth == 0 || *anchor_index == solution.len() - 1 {
+                continue;
+            }
+
+            // Ties go to the larger coordinate, whatever order the cells come up in.
+            let key_spot = key_spots.entry(*anchor_index).or_insert((0, *coord));
+            if (*depth, *coord) > *key_spot {
+                *key_spot = (*depth, *coord);
+            }
+        }
+
+        let door_count = min(door_count, min(key_spots.len(), KEY_COLORS.len()));
+        let mut anchor_indices: Vec<usize> = key_spots.keys().copied().collect();
+        anchor_indices.sort_unstable();
+        let mut anchor_indices: Vec<usize> = anchor_indices
+            .choose_multiple(rng, door_count)
+            .copied()
+            .collect();
+        anchor_indices.sort_unstable();
+
+        let mut colors = KEY_COLORS.to_vec();
+        colors.shuffle(rng);
+
+        for (i, anchor_index) in anchor_indices.iter().enumerate() {
+            // The door goes after the key's branch, but before the next key's branch.
+            let last_door_index = anchor_indices
+                .get(i + 1)
+                .map_or(solution.len() - 2, |next_anchor_index| {
+                    next_anchor_index - 1
+                });
+            let door_index = rng.gen_range(*anchor_index..=last_door_index);
+
+            let door_coord = solution[door_index];
+            let door_dir = maze
+                .neighbours(door_coord, CellReachType::Anything)
+                .into_iter()
+                .find(|(_, neighbour_coord)| *neighbour_coord == solution[door_index + 1])
+                .map(|(dir, _)| dir)
+                .ok_or(MazeError::InconsistentWalls(door_coord))?;
+
+            maze.add_door(colors[i], door_coord, door_dir)?;
+            maze.add_key(colors[i], key_spots[anchor_index].1)?;
+        }
+
+        Ok(door_count)
+    }
+
+    // Randomized depth-first search driven by the given generator. Neighbour directions are sorted
+    // before picking so the carving is reproducible for a seeded `rng`.
+    pub fn backtracker_maze_creation(maze: &mut Maze, rng: &mut impl Rng) {
+        MazeBuilder::backtracker_maze_creation_with_trace(maze, rng, &mut CarveTrace::ignored());
+    }
+
+    pub fn backtracker_maze_creation_with_trace(
+        maze: &mut Maze,
+        rng: &mut impl Rng,
+        trace: &mut CarveTrace,
+    ) {
+        if maze.cells.is_empty() {
+            return;
+        }
+
+        let start: Coord = Pair::new(0, 0);
+        maze.cells[start.index(maze.width)].mark_reached();
+
+        let mut work_stack: Vec<Coord> = vec![start];
+
+        while let Some(current_coord) = work_stack.last().copied() {
+            let neighbour_coords = maze.neighbours(current_coord, CellReachType::UnreachableOnly);
+            let mut neighbour_dirs = neighbour_coords.keys().copied().collect::<Vec<_>>();
+            neighbour_dirs.sort_unstable();
+
+            match neighbour_dirs.choose(rng) {
+                Some(dir) => {
+                    trace.carve(maze, current_coord.x, current_coord.y, *dir);
+                    work_stack.push(neighbour_coords[dir]);
+                }
+                None => {
+                    work_stack.pop();
+                }
+            }
+        }
+    }
+
     fn generate_unreachable_cells(maze: &Maze) -> HashSet<Coord> {
         let mut unreachable_cells: HashSet<Coord> = HashSet::new();
         for y in 0..maze.height {
@@ -287,3 +652,135 @@ impl MazeBuilder {
         unreachable_cells
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Solver;
+    use crate::validator::Validator;
+
+    fn tiled_maze(
+        width: usize,
+        height: usize,
+        tile_size: usize,
+        seed: u64,
+        worker_count: usize,
+    ) -> Maze {
+        let mut maze = Maze::new_full(width, height);
+        MazeBuilder::tiled_maze_creation(
+            &mut maze,
+            tile_size,
+            seed,
+            worker_count,
+            &mut CarveTrace::ignored(),
+        )
+        .unwrap();
+
+        maze
+    }
+
+    fn walls(maze: &Maze) -> Vec<[bool; 4]> {
+        maze.cells.iter().map(|cell| cell.paths).collect()
+    }
+
+    // Every cell reachable and one passage fewer than cells, so there is no room for a loop.
+    fn assert_perfect(maze: &Maze) {
+        let (_, distance_map) = Solver::build_distance_map(maze, Pair::new(0, 0)).unwrap();
+        assert!(distance_map.iter().flatten().all(|distance| *distance >= 0));
+
+        let mut passage_count = 0;
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let cell = &maze.cells[y * maze.width + x];
+                if x + 1 < maze.width && !cell.paths[EAST] {
+                    passage_count += 1;
+                }
+                if y > 0 && !cell.paths[NORTH] {
+                    passage_count += 1;
+                }
+            }
+        }
+        assert_eq!(passage_count, maze.width * maze.height - 1);
+
+        assert_eq!(Validator::validate_maze(maze), vec![]);
+    }
+
+    #[test]
+    fn tiled_mazes_only_depend_on_the_seed() {
+        let expected = walls(&tiled_maze(23, 17, 5, 42, 1));
+
+        assert_eq!(walls(&tiled_maze(23, 17, 5, 42, 1)), expected);
+        for worker_count in [2, 3, 8, 100] {
+            assert_eq!(walls(&tiled_maze(23, 17, 5, 42, worker_count)), expected);
+        }
+
+        assert_ne!(walls(&tiled_maze(23, 17, 5, 43, 1)), expected);
+    }
+
+    #[test]
+    fn tiled_mazes_are_perfect() {
+        // Sizes that divide the maze, that leave narrow tiles along the edges and that exceed it.
+        for tile_size in [1, 2, 4, 5, 7, 16, 17, 30] {
+            assert_perfect(&tiled_maze(16, 17, tile_size, 7, 4));
+        }
+    }
+
+    #[test]
+    fn rejects_empty_tiles() {
+        let mut maze = Maze::new_full(4, 4);
+
+        assert_eq!(
+            MazeBuilder::parallel_tiled_maze_creation(&mut maze, 0, 42),
+            Err(MazeError::InvalidOptions("tile size must be positive"))
+        );
+    }
+
+    fn key_door_puzzle(seed: u64) -> Maze {
+        let mut maze = tiled_maze(12, 10, 4, seed, 1);
+        let door_count = MazeBuilder::key_door_puzzle_creation(
+            &mut maze,
+            Pair::new(0, 0),
+            Pair::new(11, 9),
+            3,
+            &mut StdRng::seed_from_u64(seed),
+        )
+        .unwrap();
+        assert_eq!(door_count, 3);
+
+        maze
+    }
+
+    #[test]
+    fn key_door_puzzles_only_depend_on_the_seed() {
+        let maze = key_door_puzzle(5);
+
+        assert_eq!(key_door_puzzle(5).doors, maze.doors);
+        assert_eq!(key_door_puzzle(5).keys, maze.keys);
+    }
+
+    #[test]
+    fn key_door_puzzles_need_every_key() {
+        let (start, finish) = (Pair::new(0, 0), Pair::new(11, 9));
+
+        for seed in 0..5 {
+            let mut maze = key_door_puzzle(seed);
+            let route = Solver::key_door_path_finding_solver(&maze, start, finish).unwrap();
+
+            assert_eq!(route.key_pickups.len(), 3);
+            // Every key sits off the way to the finish, so the route has to come back from it.
+            let distinct_cells = route.path.iter().collect::<HashSet<_>>().len();
+            assert!(distinct_cells < route.path.len());
+            for key in &maze.keys {
+                assert!(!Solver::dijkstra_path_finding_solver(&maze, start, finish)
+                    .unwrap()
+                    .contains(&key.coord));
+            }
+
+            maze.keys.pop();
+            assert_eq!(
+                Solver::key_door_path_finding_solver(&maze, start, finish).map(|route| route.path),
+                Err(MazeError::NoPath { start, finish })
+            );
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::error::MazeError;
+use crate::util::Coord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpeningKind {
+    Entrance,
+    Exit,
+}
+
+// A gap in the outer wall of a maze.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub name: String,
+    pub kind: OpeningKind,
+    pub coord: Coord,
+    // Side of the cell that is open. Always `NORTH` (outwards) for a `CircleMaze`.
+    pub dir: usize,
+}
+
+// Cells of the first entrance and the first exit.
+pub fn opening_endpoints(openings: &[Opening]) -> Result<(Coord, Coord), MazeError> {
+    let find = |kind: OpeningKind| {
+        openings
+            .iter()
+            .find(|opening| opening.kind == kind)
+            .map(|opening| opening.coord)
+            .ok_or(MazeError::MissingOpening(kind))
+    };
+
+    Ok((find(OpeningKind::Entrance)?, find(OpeningKind::Exit)?))
+}
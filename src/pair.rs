@@ -1,6 +1,6 @@
 use std::hash::Hash;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Pair<T>
 where
     T: Hash,
@@ -26,7 +26,7 @@ impl Pair<i32> {
         self.y as usize * width + self.x as usize
     }
 
-    pub fn to_usize(&self) -> Pair<usize> {
+    pub fn to_usize(self) -> Pair<usize> {
         Pair::new(self.x as usize, self.y as usize)
     }
 }
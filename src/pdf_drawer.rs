@@ -0,0 +1,432 @@
+use pdf_writer::types::{LineCapStyle, LineJoinStyle};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    encoded_file::EncodedFile,
+    error::MazeError,
+    maze_builder::MazeBuilder,
+    opening::OpeningKind,
+    solver::Solver,
+    theme::{Rgb, Theme},
+    util::{EAST, NORTH, SOUTH},
+    Maze, Pair,
+};
+
+// Font sizes in points.
+const TITLE_SIZE: f32 = 14.0;
+const LABEL_SIZE: f32 = 9.0;
+
+const FONT: Name = Name(b"F1");
+
+// Helvetica advance widths in thousandths of the font size, for ' ' to '~'. Helvetica is one of
+// the fonts every PDF reader has, so nothing needs embedding, but text still has to be measured
+// to centre it.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperSize {
+    A4,
+    Letter,
+}
+
+impl PaperSize {
+    // Width and height in points, 72 to the inch.
+    pub fn dimensions(self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (595.28, 841.89),
+            PaperSize::Letter => (612.0, 792.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfOptions {
+    pub paper: PaperSize,
+    // Blank space around the page, in points.
+    pub margin: f32,
+    // Mazes per page, in a grid.
+    pub columns: usize,
+    pub rows: usize,
+    // Mazes are scaled to fit the page, so only colours and the line widths relative to
+    // `Theme::cell_size` carry over.
+    pub theme: Theme,
+    // Printed next to the entrance and the exit.
+    pub start_label: String,
+    pub finish_label: String,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            paper: PaperSize::A4,
+            margin: 36.0,
+            columns: 1,
+            rows: 1,
+            theme: Theme::print(),
+            start_label: "Start".to_string(),
+            finish_label: "Finish".to_string(),
+        }
+    }
+}
+
+// A maze to print. It needs an entrance and an exit.
+#[derive(Debug)]
+pub struct PdfPuzzle {
+    pub title: String,
+    pub maze: Maze,
+}
+
+pub type PdfDocument = EncodedFile;
+
+// Printable puzzle pages. Keys and doors are left out, since the answer key comes from the plain
+// opening solver.
+pub struct PdfDrawer;
+
+impl PdfDrawer {
+    // Puzzle pages first, then answer-key pages in the same layout with the solutions drawn in.
+    pub fn render(puzzles: &[PdfPuzzle], options: &PdfOptions) -> Result<PdfDocument, MazeError> {
+        let solutions = puzzles
+            .iter()
+            .map(|puzzle| Solver::opening_path_finding_solver(&puzzle.maze))
+            .collect::<Result<Vec<_>, _>>()?;
+        let per_page = (options.columns * options.rows).max(1);
+
+        let mut pages = vec![];
+        for show_solutions in [false, true] {
+            for (page_i, page_puzzles) in puzzles.chunks(per_page).enumerate() {
+                let mut content = Content::new();
+
+                for (slot, puzzle) in page_puzzles.iter().enumerate() {
+                    let solution = &solutions[page_i * per_page + slot];
+                    let title = if show_solutions {
+                        format!("{} - solution", puzzle.title)
+                    } else {
+                        puzzle.title.clone()
+                    };
+
+                    PdfDrawer::draw_puzzle(
+                        &mut content,
+                        &puzzle.maze,
+                        &title,
+                        show_solutions.then_some(&solution[..]),
+                        slot,
+                        options,
+                    )?;
+                }
+
+                pages.push(content.finish());
+            }
+        }
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let font_id = Ref::new(3);
+        let page_ids: Vec<Ref> = (0..pages.len())
+            .map(|i| Ref::new(4 + i as i32 * 2))
+            .collect();
+        let (page_width, page_height) = options.paper.dimensions();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        pdf.type1_font(font_id)
+            .base_font(Name(b"Helvetica"))
+            .encoding_predefined(Name(b"WinAnsiEncoding"));
+
+        for (page_id, content) in page_ids.iter().zip(&pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+
+            let mut page = pdf.page(*page_id);
+            page.parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, page_width, page_height))
+                .contents(content_id);
+            page.resources().fonts().pair(FONT, font_id);
+            page.finish();
+
+            pdf.stream(content_id, content);
+        }
+
+        Ok(EncodedFile::new(pdf.finish()))
+    }
+
+    // A puzzle book with one `width` by `height` maze per seed. Entrance and exit are put at the
+    // ends of the longest path along the edge, as far apart as the maze allows.
+    pub fn render_puzzle_book(
+        seeds: &[u64],
+        width: usize,
+        height: usize,
+        options: &PdfOptions,
+    ) -> Result<PdfDocument, MazeError> {
+        let mut puzzles = vec![];
+
+        for (i, seed) in seeds.iter().enumerate() {
+            let mut maze = Maze::new_full(width, height);
+            MazeBuilder::backtracker_maze_creation(&mut maze, &mut StdRng::seed_from_u64(*seed));
+
+            let (start, finish) = Solver::longest_boundary_path_endpoints(&maze)?;
+            let start_side = maze
+                .outer_side(start)
+                .ok_or(MazeError::InvalidOpening(start))?;
+            let finish_side = maze
+                .outer_side(finish)
+                .ok_or(MazeError::InvalidOpening(finish))?;
+            maze.add_opening("entrance", OpeningKind::Entrance, start, start_side)?;
+            maze.add_opening("exit", OpeningKind::Exit, finish, finish_side)?;
+
+            puzzles.push(PdfPuzzle {
+                title: format!("Maze {} (seed {})", i + 1, seed),
+                maze,
+            });
+        }
+
+        PdfDrawer::render(&puzzles, options)
+    }
+
+    // Draws into grid slot `slot` of the page: the title on top, then the maze as large as fits
+    // with room around it for the start and finish labels.
+    fn draw_puzzle(
+        content: &mut Content,
+        maze: &Maze,
+        title: &str,
+        solution: Option<&[Pair<usize>]>,
+        slot: usize,
+        options: &PdfOptions,
+    ) -> Result<(), MazeError> {
+        let theme = &options.theme;
+        let (page_width, page_height) = options.paper.dimensions();
+        let columns = options.columns.max(1);
+        let slot_width = (page_width - options.margin * 2.0) / columns as f32;
+        let slot_height = (page_height - options.margin * 2.0) / options.rows.max(1) as f32;
+        let slot_left = options.margin + (slot % columns) as f32 * slot_width;
+        let slot_top = page_height - options.margin - (slot / columns) as f32 * slot_height;
+
+        // PDF pages count y upwards from the bottom.
+        set_fill(content, theme.wall_color);
+        show_text(
+            content,
+            title,
+            TITLE_SIZE,
+            slot_left + (slot_width - text_width(title, TITLE_SIZE)) / 2.0,
+            slot_top - TITLE_SIZE,
+        );
+
+        let label_width = text_width(&options.start_label, LABEL_SIZE)
+            .max(text_width(&options.finish_label, LABEL_SIZE));
+        let room_x = label_width + LABEL_SIZE;
+        let room_y = LABEL_SIZE * 2.0;
+        let area_top = slot_top - TITLE_SIZE * 2.0 - room_y;
+        let area_width = slot_width - room_x * 2.0;
+        let area_height = slot_height - TITLE_SIZE * 2.0 - room_y * 2.0;
+
+        let drawing_width = theme.cell_size * maze.width as f32;
+        let drawing_height = theme.cell_size * maze.height as f32;
+        let scale = (area_width / drawing_width)
+            .min(area_height / drawing_height)
+            .max(0.0);
+        let maze_width = drawing_width * scale;
+        let maze_height = drawing_height * scale;
+        let maze_left = slot_left + (slot_width - maze_width) / 2.0;
+        let maze_top = area_top - (area_height - maze_height) / 2.0;
+
+        // The maze itself is drawn in theme units with y pointing down, like the other drawers.
+        content.save_state();
+        content.transform([scale, 0.0, 0.0, -scale, maze_left, maze_top]);
+
+        if let Some(background) = theme.background {
+            set_fill(content, background);
+            content.rect(0.0, 0.0, drawing_width, drawing_height);
+            content.fill_nonzero();
+        }
+
+        for ((x1, y1), (x2, y2)) in maze.wall_segments(theme.cell_size) {
+            content.move_to(x1, y1);
+            content.line_to(x2, y2);
+        }
+        set_stroke(content, theme.wall_color, theme.wall_width);
+        content.set_line_cap(LineCapStyle::ProjectingSquareCap);
+        content.stroke();
+
+        if let Some(solution) = solution {
+            let center = |coord: &Pair<usize>| {
+                (
+                    (coord.x as f32 + 0.5) * theme.cell_size,
+                    (coord.y as f32 + 0.5) * theme.cell_size,
+                )
+            };
+
+            if let Some(first) = solution.first() {
+                let (x, y) = center(first);
+                content.move_to(x, y);
+                for coord in solution.iter().skip(1) {
+                    let (x, y) = center(coord);
+                    content.line_to(x, y);
+                }
+                set_stroke(content, theme.path_color, theme.path_width);
+                content.set_line_cap(LineCapStyle::RoundCap);
+                content.set_line_join(LineJoinStyle::RoundJoin);
+                content.stroke();
+            }
+
+            let markers = [
+                (solution.first(), theme.start_marker),
+                (solution.last(), theme.finish_marker),
+            ];
+            for (coord, color) in markers {
+                if let (Some(coord), Some(color)) = (coord, color) {
+                    let (x, y) = center(coord);
+                    set_fill(content, color);
+                    circle(content, x, y, theme.cell_size * 0.3);
+                    content.fill_nonzero();
+                }
+            }
+        }
+
+        content.restore_state();
+
+        // Labels sit outside the opening, clear of the wall.
+        let gap = LABEL_SIZE / 2.0 + theme.wall_width * scale / 2.0;
+        let labels = [
+            (OpeningKind::Entrance, &options.start_label),
+            (OpeningKind::Exit, &options.finish_label),
+        ];
+        set_fill(content, theme.wall_color);
+        for (kind, label) in labels {
+            let opening = maze
+                .openings
+                .iter()
+                .find(|opening| opening.kind == kind)
+                .ok_or(MazeError::MissingOpening(kind))?;
+            let coord = opening.coord;
+            let center_x = maze_left + (coord.x as f32 + 0.5) * theme.cell_size * scale;
+            let center_y = maze_top - (coord.y as f32 + 0.5) * theme.cell_size * scale;
+            let width = text_width(label, LABEL_SIZE);
+            // Roughly the middle of lowercase letters, to line labels up with a cell centre.
+            let middle = LABEL_SIZE * 0.3;
+
+            let (x, y) = match opening.dir {
+                NORTH => (center_x - width / 2.0, maze_top + gap),
+                EAST => (maze_left + maze_width + gap, center_y - middle),
+                SOUTH => (
+                    center_x - width / 2.0,
+                    maze_top - maze_height - gap - LABEL_SIZE * 0.75,
+                ),
+                _ => (maze_left - gap - width, center_y - middle),
+            };
+            show_text(content, label, LABEL_SIZE, x, y);
+        }
+
+        Ok(())
+    }
+}
+
+// The font only covers ASCII here, anything else prints as '?'.
+fn pdf_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            if (' '..='~').contains(&c) {
+                c as u8
+            } else {
+                b'?'
+            }
+        })
+        .collect()
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    pdf_text(text)
+        .iter()
+        .map(|c| HELVETICA_WIDTHS[(c - b' ') as usize] as f32)
+        .sum::<f32>()
+        * size
+        / 1000.0
+}
+
+// `x` and `y` are the start of the baseline.
+fn show_text(content: &mut Content, text: &str, size: f32, x: f32, y: f32) {
+    content.begin_text();
+    content.set_font(FONT, size);
+    content.next_line(x, y);
+    content.show(Str(&pdf_text(text)));
+    content.end_text();
+}
+
+fn pdf_color((r, g, b): Rgb) -> (f32, f32, f32) {
+    (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+}
+
+fn set_fill(content: &mut Content, color: Rgb) {
+    let (r, g, b) = pdf_color(color);
+    content.set_fill_rgb(r, g, b);
+}
+
+fn set_stroke(content: &mut Content, color: Rgb, width: f32) {
+    let (r, g, b) = pdf_color(color);
+    content.set_stroke_rgb(r, g, b);
+    content.set_line_width(width);
+}
+
+// PDF has no circles, so this is four cubic curves.
+fn circle(content: &mut Content, x: f32, y: f32, r: f32) {
+    let k = r * 0.552_284_8;
+
+    content.move_to(x + r, y);
+    content.cubic_to(x + r, y + k, x + k, y + r, x, y + r);
+    content.cubic_to(x - k, y + r, x - r, y + k, x - r, y);
+    content.cubic_to(x - r, y - k, x - k, y - r, x, y - r);
+    content.cubic_to(x + k, y - r, x + r, y - k, x + r, y);
+    content.close_path();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_count(document: &PdfDocument) -> usize {
+        document
+            .as_bytes()
+            .windows(12)
+            .filter(|window| window.starts_with(b"/Type /Page") && window[11] != b's')
+            .count()
+    }
+
+    #[test]
+    fn prints_a_puzzle_and_an_answer_page_per_seed() {
+        let document =
+            PdfDrawer::render_puzzle_book(&[1, 2, 3], 6, 5, &PdfOptions::default()).unwrap();
+
+        assert!(document.as_bytes().starts_with(b"%PDF"));
+        assert_eq!(page_count(&document), 6);
+    }
+
+    #[test]
+    fn fills_the_page_grid_before_starting_a_page() {
+        let options = PdfOptions {
+            columns: 2,
+            rows: 1,
+            ..PdfOptions::default()
+        };
+        let document = PdfDrawer::render_puzzle_book(&[1, 2, 3], 6, 5, &options).unwrap();
+
+        assert_eq!(page_count(&document), 4);
+    }
+
+    #[test]
+    fn builds_the_same_book_from_the_same_seeds() {
+        let options = PdfOptions::default();
+
+        assert_eq!(
+            PdfDrawer::render_puzzle_book(&[4, 5], 8, 8, &options),
+            PdfDrawer::render_puzzle_book(&[4, 5], 8, 8, &options)
+        );
+    }
+}
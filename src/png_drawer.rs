@@ -0,0 +1,458 @@
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform,
+};
+
+use crate::{
+    circle_maze::{polar_point, CircleMaze, CircleWall},
+    encoded_file::EncodedFile,
+    error::MazeError,
+    theme::{Rgb, Theme},
+    util::{Coord, NORTH},
+    Maze, Pair,
+};
+
+// Theme sizes are CSS pixels, 96 to the inch, so a drawing has the same physical size as its SVG
+// at any DPI.
+const UNITS_PER_INCH: f32 = 96.0;
+
+pub type PngImage = EncodedFile;
+
+// Antialiased raster drawings that need no window, with the same layout as `SvgDrawer`.
+pub struct PngDrawer;
+
+impl PngDrawer {
+    // `distance_map` is what `Solver::build_distance_map` returns.
+    #[allow(unused)]
+    pub fn render(
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        distance_map: Option<(i32, &[Vec<i32>])>,
+        theme: &Theme,
+        dpi: f32,
+    ) -> Result<PngImage, MazeError> {
+        let cell_size = theme.cell_size;
+        let width = cell_size * maze.width as f32 + theme.padding * 2.0;
+        let height = cell_size * maze.height as f32 + theme.padding * 2.0;
+        let (mut pixmap, transform) = PngDrawer::new_pixmap(width, height, theme, dpi)?;
+
+        if let Some((max_distance, distance_map)) = distance_map {
+            for (y, row) in distance_map.iter().enumerate() {
+                for (x, distance) in row.iter().enumerate() {
+                    let color = match theme.heat_color(max_distance, *distance) {
+                        Some(color) => color,
+                        None => continue,
+                    };
+                    let cell = Rect::from_xywh(
+                        theme.padding + x as f32 * cell_size,
+                        theme.padding + y as f32 * cell_size,
+                        cell_size,
+                        cell_size,
+                    );
+
+                    if let Some(cell) = cell {
+                        pixmap.fill_rect(cell, &paint(color), transform, None);
+                    }
+                }
+            }
+        }
+
+        // Walls.
+        let mut walls = PathBuilder::new();
+        for ((x1, y1), (x2, y2)) in maze.wall_segments(cell_size) {
+            walls.move_to(theme.padding + x1, theme.padding + y1);
+            walls.line_to(theme.padding + x2, theme.padding + y2);
+        }
+        stroke(
+            &mut pixmap,
+            walls,
+            theme.wall_color,
+            theme.wall_width,
+            LineCap::Square,
+            transform,
+        );
+
+        // Doors are a coloured bar across their passage, keys a coloured dot.
+        for door in &maze.doors {
+            let left = theme.padding + door.coord.x as f32 * cell_size;
+            let top = theme.padding + door.coord.y as f32 * cell_size;
+            let mut bar = PathBuilder::new();
+            if door.dir == NORTH {
+                bar.move_to(left, top);
+                bar.line_to(left + cell_size, top);
+            } else {
+                bar.move_to(left + cell_size, top);
+                bar.line_to(left + cell_size, top + cell_size);
+            }
+
+            stroke(
+                &mut pixmap,
+                bar,
+                door.color.rgb(),
+                theme.wall_width * 2.0,
+                LineCap::Butt,
+                transform,
+            );
+        }
+
+        for key in &maze.keys {
+            let (x, y) = cell_center(key.coord, theme);
+            fill_circle(
+                &mut pixmap,
+                x,
+                y,
+                cell_size / 4.0,
+                key.color.rgb(),
+                transform,
+            );
+            if let Some(outline) = PathBuilder::from_circle(x, y, cell_size / 4.0) {
+                stroke_path(
+                    &mut pixmap,
+                    &outline,
+                    theme.wall_color,
+                    theme.wall_width / 2.0,
+                    LineCap::Butt,
+                    transform,
+                );
+            }
+        }
+
+        let solution_points: Vec<(f32, f32)> = solution
+            .iter()
+            .map(|coord| cell_center(*coord, theme))
+            .collect();
+        PngDrawer::draw_solution(&mut pixmap, &solution_points, cell_size, theme, transform);
+
+        PngDrawer::encode(&pixmap, dpi)
+    }
+
+    // Same layout as `SvgDrawer::render_circle_maze`, with the maze centre in the middle and
+    // `Theme::ring_height` as the ring height.
+    #[allow(unused)]
+    pub fn render_circle_maze(
+        maze: &CircleMaze,
+        solution: &[Coord],
+        distance_map: Option<(i32, &[Vec<i32>])>,
+        theme: &Theme,
+        dpi: f32,
+    ) -> Result<PngImage, MazeError> {
+        let ring_height = theme.ring_height();
+        let size = ring_height * (maze.height as f32 * 2.0 - 1.0) + theme.padding * 2.0;
+        let center = size / 2.0;
+        let point = |r: f32, alpha: f32| {
+            let (x, y) = polar_point(r, alpha);
+            (x + center, y + center)
+        };
+        let (mut pixmap, transform) = PngDrawer::new_pixmap(size, size, theme, dpi)?;
+
+        // Heat map.
+        if let Some((max_distance, distance_map)) = distance_map {
+            for (y, row) in distance_map.iter().enumerate() {
+                let cell_arc = 360.0 / row.len() as f32;
+                let r_inner = (y as f32 - 0.5) * ring_height;
+                let r_outer = (y as f32 + 0.5) * ring_height;
+
+                for (x, distance) in row.iter().enumerate() {
+                    let color = match theme.heat_color(max_distance, *distance) {
+                        Some(color) => color,
+                        None => continue,
+                    };
+
+                    // The centre cell is a disc, every other cell a ring sector.
+                    if y == 0 {
+                        fill_circle(&mut pixmap, center, center, r_outer, color, transform);
+                        continue;
+                    }
+
+                    let alpha_from = cell_arc * x as f32;
+                    let alpha_to = cell_arc * (x + 1) as f32;
+                    let mut sector = PathBuilder::new();
+                    let (start_x, start_y) = point(r_inner, alpha_from);
+                    sector.move_to(start_x, start_y);
+                    let (corner_x, corner_y) = point(r_outer, alpha_from);
+                    sector.line_to(corner_x, corner_y);
+                    arc_to(&mut sector, r_outer, alpha_from, alpha_to, &point);
+                    let (corner_x, corner_y) = point(r_inner, alpha_to);
+                    sector.line_to(corner_x, corner_y);
+                    arc_to(&mut sector, r_inner, alpha_to, alpha_from, &point);
+                    sector.close();
+
+                    if let Some(sector) = sector.finish() {
+                        pixmap.fill_path(
+                            &sector,
+                            &paint(color),
+                            FillRule::Winding,
+                            transform,
+                            None,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Walls.
+        let mut walls = PathBuilder::new();
+        for wall in maze.wall_segments(ring_height) {
+            match wall {
+                CircleWall::Arc { radius, from, to } => {
+                    let (x, y) = point(radius, from);
+                    walls.move_to(x, y);
+                    arc_to(&mut walls, radius, from, to, &point);
+                }
+                CircleWall::Radial {
+                    angle,
+                    inner_radius,
+                    outer_radius,
+                } => {
+                    let (x, y) = point(inner_radius, angle);
+                    walls.move_to(x, y);
+                    let (x, y) = point(outer_radius, angle);
+                    walls.line_to(x, y);
+                }
+            }
+        }
+        stroke(
+            &mut pixmap,
+            walls,
+            theme.wall_color,
+            theme.wall_width,
+            LineCap::Round,
+            transform,
+        );
+
+        let solution_points: Vec<(f32, f32)> = solution
+            .iter()
+            .map(|coord| {
+                let (x, y) = maze.cell_position(*coord, ring_height);
+                (x + center, y + center)
+            })
+            .collect();
+        PngDrawer::draw_solution(&mut pixmap, &solution_points, ring_height, theme, transform);
+
+        PngDrawer::encode(&pixmap, dpi)
+    }
+
+    // Records the DPI in the file, so print and layout tools pick the right size.
+    fn encode(pixmap: &Pixmap, dpi: f32) -> Result<PngImage, MazeError> {
+        let mut data = vec![];
+        let mut encoder = png::Encoder::new(&mut data, pixmap.width(), pixmap.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let pixels_per_meter = (dpi / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: pixels_per_meter,
+            yppu: pixels_per_meter,
+            unit: png::Unit::Meter,
+        }));
+
+        // The pixmap keeps premultiplied alpha, PNG wants it straight.
+        let pixels: Vec<u8> = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|_| MazeError::EncodingFailed("PNG"))?;
+
+        Ok(EncodedFile::new(data))
+    }
+
+    // A pixmap big enough for `width` by `height` drawing units at `dpi`, and the transform from
+    // drawing units to its pixels.
+    fn new_pixmap(
+        width: f32,
+        height: f32,
+        theme: &Theme,
+        dpi: f32,
+    ) -> Result<(Pixmap, Transform), MazeError> {
+        if !(dpi.is_finite() && dpi > 0.0) {
+            return Err(MazeError::InvalidOptions("DPI must be positive"));
+        }
+
+        let scale = dpi / UNITS_PER_INCH;
+        let pixel_width = ((width as f64 * scale as f64).ceil() as u64).max(1);
+        let pixel_height = ((height as f64 * scale as f64).ceil() as u64).max(1);
+        let mut pixmap = u32::try_from(pixel_width)
+            .ok()
+            .zip(u32::try_from(pixel_height).ok())
+            .and_then(|(pixel_width, pixel_height)| Pixmap::new(pixel_width, pixel_height))
+            .ok_or(MazeError::ImageTooLarge {
+                width: pixel_width,
+                height: pixel_height,
+            })?;
+
+        if let Some((r, g, b)) = theme.background {
+            pixmap.fill(Color::from_rgba8(r, g, b, 255));
+        }
+
+        Ok((pixmap, Transform::from_scale(scale, scale)))
+    }
+
+    // The path through the cell centres in `points`, with the start and finish markers.
+    fn draw_solution(
+        pixmap: &mut Pixmap,
+        points: &[(f32, f32)],
+        cell_size: f32,
+        theme: &Theme,
+        transform: Transform,
+    ) {
+        if points.len() > 1 {
+            let mut path = PathBuilder::new();
+            path.move_to(points[0].0, points[0].1);
+            for (x, y) in points.iter().skip(1) {
+                path.line_to(*x, *y);
+            }
+
+            stroke(
+                pixmap,
+                path,
+                theme.path_color,
+                theme.path_width,
+                LineCap::Round,
+                transform,
+            );
+        }
+
+        let markers = [
+            (points.first(), theme.start_marker),
+            (points.last(), theme.finish_marker),
+        ];
+        for (marker_point, color) in markers {
+            if let (Some((x, y)), Some(color)) = (marker_point, color) {
+                fill_circle(pixmap, *x, *y, cell_size * 0.3, color, transform);
+            }
+        }
+    }
+}
+
+fn cell_center(coord: Pair<usize>, theme: &Theme) -> (f32, f32) {
+    (
+        theme.padding + (coord.x as f32 + 0.5) * theme.cell_size,
+        theme.padding + (coord.y as f32 + 0.5) * theme.cell_size,
+    )
+}
+
+fn paint((r, g, b): Rgb) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, 255);
+    paint.anti_alias = true;
+    paint
+}
+
+fn fill_circle(pixmap: &mut Pixmap, x: f32, y: f32, r: f32, color: Rgb, transform: Transform) {
+    if let Some(circle) = PathBuilder::from_circle(x, y, r) {
+        pixmap.fill_path(&circle, &paint(color), FillRule::Winding, transform, None);
+    }
+}
+
+// Empty paths draw nothing.
+fn stroke(
+    pixmap: &mut Pixmap,
+    path: PathBuilder,
+    color: Rgb,
+    width: f32,
+    line_cap: LineCap,
+    transform: Transform,
+) {
+    if let Some(path) = path.finish() {
+        stroke_path(pixmap, &path, color, width, line_cap, transform);
+    }
+}
+
+fn stroke_path(
+    pixmap: &mut Pixmap,
+    path: &tiny_skia::Path,
+    color: Rgb,
+    width: f32,
+    line_cap: LineCap,
+    transform: Transform,
+) {
+    let stroke = Stroke {
+        width,
+        line_cap,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    };
+
+    pixmap.stroke_path(path, &paint(color), &stroke, transform, None);
+}
+
+// Arc around the maze centre at radius `r`, from the current point at angle `alpha_from` to
+// `alpha_to`, as cubic curves of at most a quarter turn each.
+fn arc_to(
+    path: &mut PathBuilder,
+    r: f32,
+    alpha_from: f32,
+    alpha_to: f32,
+    point: &impl Fn(f32, f32) -> (f32, f32),
+) {
+    let span = alpha_to - alpha_from;
+    let segments = (span.abs() / 90.0).ceil().max(1.0) as usize;
+    let step = span / segments as f32;
+    // Control points sit along the tangent, which is the radius a quarter turn further on.
+    let handle = 4.0 / 3.0 * (step.to_radians() / 4.0).tan();
+    let (center_x, center_y) = point(0.0, 0.0);
+    let tangent = |alpha: f32| {
+        let (x, y) = point(r, alpha + 90.0);
+        ((x - center_x) * handle, (y - center_y) * handle)
+    };
+
+    for i in 0..segments {
+        let from = alpha_from + step * i as f32;
+        let to = from + step;
+        let (from_x, from_y) = point(r, from);
+        let (to_x, to_y) = point(r, to);
+        let (from_dx, from_dy) = tangent(from);
+        let (to_dx, to_dy) = tangent(to);
+
+        path.cubic_to(
+            from_x + from_dx,
+            from_y + from_dy,
+            to_x - to_dx,
+            to_y - to_dy,
+            to_x,
+            to_y,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze_builder::MazeBuilder;
+
+    fn carved_maze() -> Maze {
+        let mut maze = Maze::new_full(4, 3);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, 3).unwrap();
+        maze
+    }
+
+    #[test]
+    fn writes_png_files() {
+        let image = PngDrawer::render(&carved_maze(), &[], None, &Theme::print(), 96.0).unwrap();
+
+        assert!(image.as_bytes().starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn rejects_dpi_that_is_not_positive() {
+        let maze = carved_maze();
+
+        for dpi in [0.0, -96.0, f32::NAN] {
+            assert_eq!(
+                PngDrawer::render(&maze, &[], None, &Theme::print(), dpi),
+                Err(MazeError::InvalidOptions("DPI must be positive"))
+            );
+            assert_eq!(
+                PngDrawer::render_circle_maze(&CircleMaze::new(3), &[], None, &Theme::print(), dpi),
+                Err(MazeError::InvalidOptions("DPI must be positive"))
+            );
+        }
+    }
+}
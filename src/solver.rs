@@ -1,14 +1,149 @@
-use std::collections::VecDeque;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 
 use crate::circle_maze::CircleMaze;
+use crate::error::MazeError;
+use crate::key_door::Key;
 use crate::util::*;
 use crate::{Maze, Pair};
 
+#[allow(unused)]
+#[derive(Debug, Clone, Copy)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Zero,
+}
+
+impl Heuristic {
+    fn estimate(&self, from: Pair<usize>, to: Pair<usize>) -> f32 {
+        let dx = from.x.abs_diff(to.x) as f32;
+        let dy = from.y.abs_diff(to.y) as f32;
+
+        match self {
+            Heuristic::Manhattan => dx + dy,
+            Heuristic::Euclidean => dx.hypot(dy),
+            Heuristic::Zero => 0.0,
+        }
+    }
+}
+
+#[allow(unused)]
+#[derive(Debug, Clone, Copy)]
+pub enum CircleMazeHeuristic {
+    Polar,
+    Zero,
+}
+
+// Parent direction map entries of the bidirectional search. Every other entry is the direction
+// towards the parent cell.
+const UNREACHED: u8 = u8::MAX;
+const ROOT: u8 = 4;
+
+// Checkpoint counts up to this are ordered exactly, larger sets heuristically.
+const EXACT_CHECKPOINT_LIMIT: usize = 12;
+
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct CheckpointTour {
+    pub path: Vec<Pair<usize>>,
+    // Checkpoints in the order the path visits them.
+    pub checkpoints: Vec<Pair<usize>>,
+    pub length: usize,
+}
+
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct KeyDoorRoute {
+    // Cells walked from start to finish. Cells repeat where the route has to come back from a
+    // key.
+    pub path: Vec<Pair<usize>>,
+    // Keys in the order they are picked up.
+    pub key_pickups: Vec<Key>,
+}
+
+// One expansion of a search: the cell taken off the frontier and the cells that joined the frontier
+// because of it.
+#[derive(Debug, Clone)]
+pub struct SearchStep {
+    pub expanded: Pair<usize>,
+    pub discovered: Vec<Pair<usize>>,
+}
+
+// Everything a search looked at, in order. Only the changes are stored per step, the frontier and
+// the visited set at any step are rebuilt from them.
+#[derive(Debug, Clone)]
+pub struct SearchTrace {
+    pub start: Pair<usize>,
+    pub steps: Vec<SearchStep>,
+}
+
+impl SearchTrace {
+    // Cells reached during the first `step_count` steps, the start included.
+    #[allow(unused)]
+    pub fn visited_after(&self, step_count: usize) -> HashSet<Pair<usize>> {
+        let mut visited: HashSet<Pair<usize>> = HashSet::from([self.start]);
+        for step in self.steps.iter().take(step_count) {
+            visited.extend(step.discovered.iter().copied());
+        }
+
+        visited
+    }
+
+    // Cells reached but not expanded yet after the first `step_count` steps.
+    #[allow(unused)]
+    pub fn frontier_after(&self, step_count: usize) -> HashSet<Pair<usize>> {
+        let mut frontier = self.visited_after(step_count);
+        for step in self.steps.iter().take(step_count) {
+            frontier.remove(&step.expanded);
+        }
+
+        frontier
+    }
+}
+
+// Open set entry of A*. Ordered so `BinaryHeap` pops the lowest estimate first, preferring the
+// deeper node on ties.
+struct OpenNode {
+    estimate: f32,
+    distance: i32,
+    coord: Pair<usize>,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then(self.distance.cmp(&other.distance))
+    }
+}
+
 pub struct Solver;
 
 impl Solver {
     #[allow(unused)]
-    pub fn build_distance_map(maze: &Maze, start: Pair<usize>) -> (i32, Vec<Vec<i32>>) {
+    pub fn build_distance_map(
+        maze: &Maze,
+        start: Pair<usize>,
+    ) -> Result<(i32, Vec<Vec<i32>>), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+
         let mut distance_map: Vec<Vec<i32>> = vec![vec![-1; maze.width]; maze.height];
         let mut max_distance = 0;
 
@@ -17,10 +152,10 @@ impl Solver {
         distance_map[start.y][start.x] = 0;
 
         while let Some(current_coord) = work_queue.pop_front() {
-            for dir in 0..4 {
+            for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
                 let neighbour_coord = Pair::new(
-                    current_coord.x as i32 + NEIGHBOUR_MAP[dir][0],
-                    current_coord.y as i32 + NEIGHBOUR_MAP[dir][1],
+                    current_coord.x as i32 + offset[0],
+                    current_coord.y as i32 + offset[1],
                 );
 
                 if neighbour_coord.x < 0
@@ -31,8 +166,7 @@ impl Solver {
                     continue;
                 }
 
-                let current_cell =
-                    &maze.cells[(current_coord.y * maze.width + current_coord.x) as usize];
+                let current_cell = &maze.cells[current_coord.y * maze.width + current_coord.x];
                 if current_cell.paths[dir] {
                     // It's a wall.
                     continue;
@@ -44,7 +178,7 @@ impl Solver {
 
                 if neighbour_distance != -1 {
                     if neighbour_distance > current_distance + 1 {
-                        panic!("This was not suppose to happen with breadth first search.");
+                        return Err(MazeError::InconsistentWalls(current_coord));
                     }
                     // Already visited.
                     continue;
@@ -60,7 +194,129 @@ impl Solver {
             }
         }
 
-        return (max_distance, distance_map);
+        Ok((max_distance, distance_map))
+    }
+
+    // Solves from the maze's entrance to its exit.
+    #[allow(unused)]
+    pub fn opening_path_finding_solver(maze: &Maze) -> Result<Vec<Pair<usize>>, MazeError> {
+        let (start, finish) = maze.opening_endpoints()?;
+
+        Solver::dijkstra_path_finding_solver(maze, start, finish)
+    }
+
+    #[allow(unused)]
+    pub fn opening_path_finding_solver_for_circle_maze(
+        maze: &CircleMaze,
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        let (start, finish) = maze.opening_endpoints()?;
+
+        Solver::dijkstra_path_finding_solver_for_circle_maze(maze, start, finish)
+    }
+
+    // Ends of the longest shortest path, found with a double breadth first search: the cell
+    // farthest from any cell is one end, the cell farthest from that end is the other. Exact for
+    // perfect mazes, a good approximation once there are loops.
+    #[allow(unused)]
+    pub fn longest_path_endpoints(maze: &Maze) -> Result<(Pair<usize>, Pair<usize>), MazeError> {
+        let (_, distance_map) = Solver::build_distance_map(maze, Pair::new(0, 0))?;
+        let start = Solver::farthest_cell(&distance_map, |_| true);
+
+        let (_, distance_map) = Solver::build_distance_map(maze, start)?;
+        let finish = Solver::farthest_cell(&distance_map, |_| true);
+
+        Ok((start, finish))
+    }
+
+    // Same double sweep, with both ends restricted to cells on the outer boundary, so they can
+    // serve as entrances.
+    #[allow(unused)]
+    pub fn longest_boundary_path_endpoints(
+        maze: &Maze,
+    ) -> Result<(Pair<usize>, Pair<usize>), MazeError> {
+        let is_on_boundary = |coord: Pair<usize>| {
+            coord.x == 0 || coord.y == 0 || coord.x == maze.width - 1 || coord.y == maze.height - 1
+        };
+
+        let (_, distance_map) = Solver::build_distance_map(maze, Pair::new(0, 0))?;
+        let start = Solver::farthest_cell(&distance_map, is_on_boundary);
+
+        let (_, distance_map) = Solver::build_distance_map(maze, start)?;
+        let finish = Solver::farthest_cell(&distance_map, is_on_boundary);
+
+        Ok((start, finish))
+    }
+
+    #[allow(unused)]
+    pub fn longest_path_endpoints_for_circle_maze(
+        maze: &CircleMaze,
+    ) -> Result<(Pair<usize>, Pair<usize>), MazeError> {
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(maze, Pair::new(0, 0))?;
+        let start = Solver::farthest_cell(&distance_map, |_| true);
+
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(maze, start)?;
+        let finish = Solver::farthest_cell(&distance_map, |_| true);
+
+        Ok((start, finish))
+    }
+
+    // The boundary of a circle maze is its outer ring.
+    #[allow(unused)]
+    pub fn longest_boundary_path_endpoints_for_circle_maze(
+        maze: &CircleMaze,
+    ) -> Result<(Pair<usize>, Pair<usize>), MazeError> {
+        let is_on_boundary = |coord: Pair<usize>| coord.y == maze.height - 1;
+
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(maze, Pair::new(0, 0))?;
+        let start = Solver::farthest_cell(&distance_map, is_on_boundary);
+
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(maze, start)?;
+        let finish = Solver::farthest_cell(&distance_map, is_on_boundary);
+
+        Ok((start, finish))
+    }
+
+    // Distances are returned per ring, indexed the same way as `CircleMaze::cells`.
+    #[allow(unused)]
+    pub fn build_distance_map_for_circle_maze(
+        maze: &CircleMaze,
+        start: Pair<usize>,
+    ) -> Result<(i32, Vec<Vec<i32>>), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+
+        let mut distance_map: Vec<Vec<i32>> = vec![];
+        for h in 0..maze.height {
+            distance_map.push(vec![-1; maze.cells[h].len()]);
+        }
+        let mut max_distance = 0;
+
+        let mut work_queue: VecDeque<Pair<usize>> = VecDeque::new();
+        work_queue.push_back(start);
+        distance_map[start.y][start.x] = 0;
+
+        while let Some(current_coord) = work_queue.pop_front() {
+            let neighbours = maze.neighbours(current_coord, CellReachType::ReachableOnly);
+
+            for (dir, neighbour_coord) in neighbours {
+                if !maze.cell_at(current_coord)?.is_open_at(dir) {
+                    continue;
+                }
+
+                if distance_map[neighbour_coord.y][neighbour_coord.x] != -1 {
+                    // Already visited.
+                    continue;
+                }
+
+                let neighbour_distance = distance_map[current_coord.y][current_coord.x] + 1;
+                distance_map[neighbour_coord.y][neighbour_coord.x] = neighbour_distance;
+                max_distance = std::cmp::max(max_distance, neighbour_distance);
+
+                work_queue.push_back(neighbour_coord);
+            }
+        }
+
+        Ok((max_distance, distance_map))
     }
 
     #[allow(unused)]
@@ -68,7 +324,38 @@ impl Solver {
         maze: &Maze,
         start: Pair<usize>,
         finish: Pair<usize>,
-    ) -> Vec<Pair<usize>> {
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        Solver::breadth_first_search(maze, start, finish, None)
+    }
+
+    // Same search as `dijkstra_path_finding_solver`, also recording how the wavefront spreads.
+    #[allow(unused)]
+    pub fn dijkstra_path_finding_solver_with_trace(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<(Vec<Pair<usize>>, SearchTrace), MazeError> {
+        let mut trace = SearchTrace {
+            start,
+            steps: vec![],
+        };
+        let path = Solver::breadth_first_search(maze, start, finish, Some(&mut trace))?;
+
+        Ok((path, trace))
+    }
+
+    // Both Dijkstra solvers, the steps are only recorded when there is a trace to put them in.
+    fn breadth_first_search(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        mut trace: Option<&mut SearchTrace>,
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
         let mut distance_map: Vec<Vec<i32>> = vec![vec![-1; maze.width]; maze.height];
 
         let mut work_queue: VecDeque<Pair<usize>> = VecDeque::new();
@@ -78,10 +365,12 @@ impl Solver {
         let mut completed = false;
 
         while let Some(current_coord) = work_queue.pop_front() {
-            for dir in 0..4 {
+            let mut discovered: Vec<Pair<usize>> = vec![];
+
+            for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
                 let neighbour_coord = Pair::new(
-                    current_coord.x as i32 + NEIGHBOUR_MAP[dir][0],
-                    current_coord.y as i32 + NEIGHBOUR_MAP[dir][1],
+                    current_coord.x as i32 + offset[0],
+                    current_coord.y as i32 + offset[1],
                 );
 
                 if neighbour_coord.x < 0
@@ -92,8 +381,7 @@ impl Solver {
                     continue;
                 }
 
-                let current_cell =
-                    &maze.cells[(current_coord.y * maze.width + current_coord.x) as usize];
+                let current_cell = &maze.cells[current_coord.y * maze.width + current_coord.x];
                 if current_cell.paths[dir] {
                     // It's a wall.
                     continue;
@@ -105,7 +393,7 @@ impl Solver {
 
                 if neighbour_distance != -1 {
                     if neighbour_distance > current_distance + 1 {
-                        panic!("This was not suppose to happen with breadth first search.");
+                        return Err(MazeError::InconsistentWalls(current_coord));
                     }
                     // Already visited.
                     continue;
@@ -115,6 +403,9 @@ impl Solver {
                     current_distance + 1;
                 let neighbour_coord_usize =
                     Pair::new(neighbour_coord.x as usize, neighbour_coord.y as usize);
+                if trace.is_some() {
+                    discovered.push(neighbour_coord_usize);
+                }
 
                 if neighbour_coord_usize == finish {
                     completed = true;
@@ -124,70 +415,19 @@ impl Solver {
                 work_queue.push_back(neighbour_coord_usize);
             }
 
-            if completed {
-                break;
-            }
-        }
-
-        // Extract path.
-        let mut current_distance = distance_map[finish.y][finish.x];
-        if current_distance == -1 {
-            panic!("Haven't found path.");
-        }
-
-        let mut path: Vec<Pair<usize>> = vec![];
-        let mut current_coord = finish;
-        let mut found_next;
-
-        path.push(current_coord);
-
-        loop {
-            found_next = false;
-
-            for dir in 0..4 {
-                let neighbour_coord = Pair::new(
-                    current_coord.x as i32 + NEIGHBOUR_MAP[dir][0],
-                    current_coord.y as i32 + NEIGHBOUR_MAP[dir][1],
-                );
-
-                if neighbour_coord.x < 0
-                    || neighbour_coord.y < 0
-                    || neighbour_coord.x >= maze.width as i32
-                    || neighbour_coord.y >= maze.height as i32
-                {
-                    continue;
-                }
-
-                if distance_map[neighbour_coord.y as usize][neighbour_coord.x as usize]
-                    == current_distance - 1
-                    && !maze.cells[current_coord.y * maze.width + current_coord.x].paths[dir]
-                {
-                    current_distance -= 1;
-                    current_coord =
-                        Pair::new(neighbour_coord.x as usize, neighbour_coord.y as usize);
-
-                    path.push(current_coord);
-
-                    found_next = true;
-
-                    break;
-                }
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.steps.push(SearchStep {
+                    expanded: current_coord,
+                    discovered,
+                });
             }
 
-            if current_coord == start {
+            if completed {
                 break;
             }
-
-            if found_next {
-                continue;
-            }
-
-            panic!("Missing previous step.");
         }
 
-        path.reverse();
-
-        return path;
+        Solver::extract_path(maze, &distance_map, start, finish, |_, _| 1)
     }
 
     #[allow(unused)]
@@ -195,7 +435,12 @@ impl Solver {
         maze: &CircleMaze,
         start: Pair<usize>,
         finish: Pair<usize>,
-    ) -> Vec<Pair<usize>> {
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
         let mut distance_map: Vec<Vec<i32>> = vec![];
         for h in 0..maze.height {
             distance_map.push(vec![-1; maze.cells[h].len()]);
@@ -211,24 +456,22 @@ impl Solver {
             let neighbours = maze.neighbours(current_coord, CellReachType::ReachableOnly);
 
             for (dir, neighbour_coord) in neighbours {
-                if !maze.cell_at(current_coord).is_open_at(dir) {
+                if !maze.cell_at(current_coord)?.is_open_at(dir) {
                     continue;
                 }
 
                 let current_distance = distance_map[current_coord.y][current_coord.x];
-                let neighbour_distance =
-                    distance_map[neighbour_coord.y as usize][neighbour_coord.x as usize];
+                let neighbour_distance = distance_map[neighbour_coord.y][neighbour_coord.x];
 
                 if neighbour_distance != -1 {
                     if neighbour_distance > current_distance + 1 {
-                        panic!("This was not suppose to happen with breadth first search.");
+                        return Err(MazeError::InconsistentWalls(current_coord));
                     }
                     // Already visited.
                     continue;
                 }
 
-                distance_map[neighbour_coord.y as usize][neighbour_coord.x as usize] =
-                    current_distance + 1;
+                distance_map[neighbour_coord.y][neighbour_coord.x] = current_distance + 1;
 
                 if neighbour_coord == finish {
                     completed = true;
@@ -243,49 +486,1537 @@ impl Solver {
             }
         }
 
-        // Extract path.
-        let mut current_distance = distance_map[finish.y][finish.x];
-        if current_distance == -1 {
-            panic!("Haven't found path.");
-        }
+        Solver::extract_circle_maze_path(maze, &distance_map, start, finish)
+    }
 
-        let mut path: Vec<Pair<usize>> = vec![];
-        let mut current_coord = finish;
-        let mut found_next;
+    // Same as `build_distance_map`, with distances measured as the total cost of the cells entered
+    // and the passages crossed.
+    #[allow(unused)]
+    pub fn build_cost_map(
+        maze: &Maze,
+        start: Pair<usize>,
+    ) -> Result<(i32, Vec<Vec<i32>>), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
 
-        path.push(current_coord);
+        let cost_map = Solver::build_weighted_distance_map(maze, start, None)?;
+        let max_cost = cost_map.iter().flatten().copied().max().unwrap_or(0);
 
-        loop {
-            found_next = false;
-            let neighbours = maze.neighbours(current_coord, CellReachType::ReachableOnly);
+        Ok((max_cost, cost_map))
+    }
 
-            for (dir, neighbour_coord) in neighbours {
-                if distance_map[neighbour_coord.y as usize][neighbour_coord.x as usize]
-                    == current_distance - 1
-                    && maze.cell_at(current_coord).is_open_at(dir)
-                {
-                    current_distance -= 1;
-                    current_coord = neighbour_coord;
-                    path.push(current_coord);
-                    found_next = true;
+    // Returns the minimum total cost of reaching `finish` along with the path.
+    #[allow(unused)]
+    pub fn weighted_dijkstra_path_finding_solver(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<(i32, Vec<Pair<usize>>), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
 
-                    break;
-                }
+        let cost_map = Solver::build_weighted_distance_map(maze, start, Some(finish))?;
+        // Steps too expensive to fit never lie on a path, the cost map would have overflowed.
+        let path = Solver::extract_path(maze, &cost_map, start, finish, |coord, dir| {
+            Solver::entry_cost(maze, coord, dir).unwrap_or(i32::MAX)
+        })?;
+
+        Ok((cost_map[finish.y][finish.x], path))
+    }
+
+    #[allow(unused)]
+    pub fn a_star_path_finding_solver(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        heuristic: Heuristic,
+    ) -> Result<(Vec<Pair<usize>>, usize), MazeError> {
+        let (path, trace) =
+            Solver::a_star_path_finding_solver_with_trace(maze, start, finish, heuristic)?;
+
+        Ok((path, trace.steps.len()))
+    }
+
+    #[allow(unused)]
+    pub fn a_star_path_finding_solver_with_trace(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        heuristic: Heuristic,
+    ) -> Result<(Vec<Pair<usize>>, SearchTrace), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let mut distance_map: Vec<Vec<i32>> = vec![vec![-1; maze.width]; maze.height];
+        let mut expanded_map: Vec<Vec<bool>> = vec![vec![false; maze.width]; maze.height];
+        let mut trace = SearchTrace {
+            start,
+            steps: vec![],
+        };
+
+        let mut open_queue: BinaryHeap<OpenNode> = BinaryHeap::new();
+        open_queue.push(OpenNode {
+            estimate: heuristic.estimate(start, finish),
+            distance: 0,
+            coord: start,
+        });
+        distance_map[start.y][start.x] = 0;
+
+        while let Some(current) = open_queue.pop() {
+            let current_coord = current.coord;
+            if expanded_map[current_coord.y][current_coord.x] {
+                // Stale queue entry.
+                continue;
             }
+            expanded_map[current_coord.y][current_coord.x] = true;
+            trace.steps.push(SearchStep {
+                expanded: current_coord,
+                discovered: vec![],
+            });
 
-            if current_coord == start {
+            if current_coord == finish {
                 break;
             }
 
-            if found_next {
-                continue;
+            for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
+                let neighbour_coord = Pair::new(
+                    current_coord.x as i32 + offset[0],
+                    current_coord.y as i32 + offset[1],
+                );
+
+                if neighbour_coord.x < 0
+                    || neighbour_coord.y < 0
+                    || neighbour_coord.x >= maze.width as i32
+                    || neighbour_coord.y >= maze.height as i32
+                {
+                    continue;
+                }
+
+                if maze.cell_at(current_coord)?.paths[dir] {
+                    // It's a wall.
+                    continue;
+                }
+
+                let neighbour_coord = neighbour_coord.to_usize();
+                let neighbour_distance = distance_map[neighbour_coord.y][neighbour_coord.x];
+                if neighbour_distance != -1 && neighbour_distance <= current.distance + 1 {
+                    continue;
+                }
+
+                distance_map[neighbour_coord.y][neighbour_coord.x] = current.distance + 1;
+                trace
+                    .steps
+                    .last_mut()
+                    .expect("Missing step")
+                    .discovered
+                    .push(neighbour_coord);
+                open_queue.push(OpenNode {
+                    estimate: (current.distance + 1) as f32
+                        + heuristic.estimate(neighbour_coord, finish),
+                    distance: current.distance + 1,
+                    coord: neighbour_coord,
+                });
+            }
+        }
+
+        Ok((
+            Solver::extract_path(maze, &distance_map, start, finish, |_, _| 1)?,
+            trace,
+        ))
+    }
+
+    // Breadth first search from both ends at once, always growing the smaller frontier by a whole
+    // layer, until the two searches touch. The first touch is already on a shortest path. Each
+    // side keeps one byte per cell, the direction back to the cell it was reached from, so very
+    // large mazes stay affordable.
+    #[allow(unused)]
+    pub fn bidirectional_path_finding_solver(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<(Vec<Pair<usize>>, usize), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let width = maze.width;
+        let mut parent_dirs: [Vec<u8>; 2] = [
+            vec![UNREACHED; width * maze.height],
+            vec![UNREACHED; width * maze.height],
+        ];
+        parent_dirs[0][start.index(width)] = ROOT;
+        parent_dirs[1][finish.index(width)] = ROOT;
+
+        let mut frontiers: [Vec<usize>; 2] = [vec![start.index(width)], vec![finish.index(width)]];
+        let mut expanded_count = 0;
+        let mut meeting_index = (start == finish).then(|| start.index(width));
+
+        while meeting_index.is_none() {
+            let side = if frontiers[0].len() <= frontiers[1].len() {
+                0
+            } else {
+                1
+            };
+            if frontiers[side].is_empty() {
+                return Err(MazeError::NoPath { start, finish });
             }
 
-            panic!("Missing previous step.");
+            let mut next_frontier: Vec<usize> = vec![];
+            'layer: for current_index in std::mem::take(&mut frontiers[side]) {
+                expanded_count += 1;
+                let current_x = (current_index % width) as i32;
+                let current_y = (current_index / width) as i32;
+
+                for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
+                    let neighbour_coord = Pair::new(current_x + offset[0], current_y + offset[1]);
+
+                    if neighbour_coord.x < 0
+                        || neighbour_coord.y < 0
+                        || neighbour_coord.x >= width as i32
+                        || neighbour_coord.y >= maze.height as i32
+                        || maze.cells[current_index].paths[dir]
+                    {
+                        continue;
+                    }
+
+                    let neighbour_index = neighbour_coord.index(width);
+                    if parent_dirs[side][neighbour_index] != UNREACHED {
+                        continue;
+                    }
+                    parent_dirs[side][neighbour_index] = ((dir + 2) % 4) as u8;
+
+                    if parent_dirs[1 - side][neighbour_index] != UNREACHED {
+                        meeting_index = Some(neighbour_index);
+                        break 'layer;
+                    }
+
+                    next_frontier.push(neighbour_index);
+                }
+            }
+            frontiers[side] = next_frontier;
         }
 
+        let meeting_index = meeting_index.expect("Missing meeting cell");
+        let meeting_coord = Pair::new(meeting_index % width, meeting_index / width);
+
+        let mut path = Solver::follow_parent_dirs(&parent_dirs[0], width, meeting_coord);
         path.reverse();
+        path.extend(
+            Solver::follow_parent_dirs(&parent_dirs[1], width, meeting_coord)
+                .into_iter()
+                .skip(1),
+        );
+
+        Ok((path, expanded_count))
+    }
+
+    #[allow(unused)]
+    pub fn a_star_path_finding_solver_for_circle_maze(
+        maze: &CircleMaze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        heuristic: CircleMazeHeuristic,
+    ) -> Result<(Vec<Pair<usize>>, usize), MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let mut distance_map: Vec<Vec<i32>> = vec![];
+        let mut expanded_map: Vec<Vec<bool>> = vec![];
+        for h in 0..maze.height {
+            distance_map.push(vec![-1; maze.cells[h].len()]);
+            expanded_map.push(vec![false; maze.cells[h].len()]);
+        }
+        let mut expanded_count = 0;
+
+        // Dividing by the longest possible step keeps the polar estimate admissible.
+        let finish_position = maze.cell_position(finish, 1.0);
+        let max_step_length = Solver::circle_maze_max_step_length(maze);
+        let estimate = |coord: Pair<usize>| match heuristic {
+            CircleMazeHeuristic::Polar => {
+                let (x, y) = maze.cell_position(coord, 1.0);
+                (x - finish_position.0).hypot(y - finish_position.1) / max_step_length
+            }
+            CircleMazeHeuristic::Zero => 0.0,
+        };
+
+        let mut open_queue: BinaryHeap<OpenNode> = BinaryHeap::new();
+        open_queue.push(OpenNode {
+            estimate: estimate(start),
+            distance: 0,
+            coord: start,
+        });
+        distance_map[start.y][start.x] = 0;
+
+        while let Some(current) = open_queue.pop() {
+            let current_coord = current.coord;
+            if expanded_map[current_coord.y][current_coord.x] {
+                // Stale queue entry.
+                continue;
+            }
+            expanded_map[current_coord.y][current_coord.x] = true;
+            expanded_count += 1;
+
+            if current_coord == finish {
+                break;
+            }
+
+            let neighbours = maze.neighbours(current_coord, CellReachType::ReachableOnly);
+            for (dir, neighbour_coord) in neighbours {
+                if !maze.cell_at(current_coord)?.is_open_at(dir) {
+                    continue;
+                }
+
+                let neighbour_distance = distance_map[neighbour_coord.y][neighbour_coord.x];
+                if neighbour_distance != -1 && neighbour_distance <= current.distance + 1 {
+                    continue;
+                }
+
+                distance_map[neighbour_coord.y][neighbour_coord.x] = current.distance + 1;
+                open_queue.push(OpenNode {
+                    estimate: (current.distance + 1) as f32 + estimate(neighbour_coord),
+                    distance: current.distance + 1,
+                    coord: neighbour_coord,
+                });
+            }
+        }
+
+        Ok((
+            Solver::extract_circle_maze_path(maze, &distance_map, start, finish)?,
+            expanded_count,
+        ))
+    }
+
+    // Number of distinct simple paths (no cell visited twice) from start to finish. Grows
+    // exponentially with the number of loops, so counting stops at `cap`.
+    #[allow(unused)]
+    pub fn count_simple_paths(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        cap: usize,
+    ) -> Result<usize, MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let mut path_count = 0;
+        let mut on_path: Vec<Vec<bool>> = vec![vec![false; maze.width]; maze.height];
+        on_path[start.y][start.x] = true;
+
+        // Depth first search. Each entry holds a cell on the current path and its exits not yet
+        // tried.
+        let mut work_stack: Vec<(Pair<usize>, Vec<Pair<usize>>)> =
+            vec![(start, Solver::open_neighbours(maze, start))];
+
+        while path_count < cap {
+            let (current_coord, exits) = match work_stack.last_mut() {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            if *current_coord == finish {
+                path_count += 1;
+                on_path[current_coord.y][current_coord.x] = false;
+                work_stack.pop();
+                continue;
+            }
+
+            match exits.pop() {
+                Some(neighbour_coord) => {
+                    if !on_path[neighbour_coord.y][neighbour_coord.x] {
+                        on_path[neighbour_coord.y][neighbour_coord.x] = true;
+                        work_stack.push((
+                            neighbour_coord,
+                            Solver::open_neighbours(maze, neighbour_coord),
+                        ));
+                    }
+                }
+                None => {
+                    on_path[current_coord.y][current_coord.x] = false;
+                    work_stack.pop();
+                }
+            }
+        }
+
+        Ok(path_count)
+    }
+
+    // Number of distinct paths of the shortest length. Every cell's count is the sum of the
+    // counts of its neighbours one breadth first search layer closer to the start.
+    #[allow(unused)]
+    pub fn count_shortest_paths(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<u64, MazeError> {
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+        let (max_distance, distance_map) = Solver::build_distance_map(maze, start)?;
+
+        if distance_map[finish.y][finish.x] == -1 {
+            return Err(MazeError::NoPath { start, finish });
+        }
+
+        let mut layers: Vec<Vec<Pair<usize>>> = vec![vec![]; max_distance as usize + 1];
+        for (y, row) in distance_map.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                if *distance >= 0 {
+                    layers[*distance as usize].push(Pair::new(x, y));
+                }
+            }
+        }
+
+        let mut path_counts: Vec<Vec<u64>> = vec![vec![0; maze.width]; maze.height];
+        path_counts[start.y][start.x] = 1;
+        for (distance, layer) in layers.iter().enumerate().skip(1) {
+            for coord in layer {
+                path_counts[coord.y][coord.x] = Solver::open_neighbours(maze, *coord)
+                    .iter()
+                    .filter(|neighbour_coord| {
+                        distance_map[neighbour_coord.y][neighbour_coord.x] == distance as i32 - 1
+                    })
+                    .fold(0u64, |sum, neighbour_coord| {
+                        sum.saturating_add(path_counts[neighbour_coord.y][neighbour_coord.x])
+                    });
+            }
+        }
+
+        Ok(path_counts[finish.y][finish.x])
+    }
+
+    // Yen's algorithm: the `k` shortest simple paths, shortest first. Returns fewer when the maze
+    // does not have that many.
+    #[allow(unused)]
+    pub fn k_shortest_paths(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        k: usize,
+    ) -> Result<Vec<Vec<Pair<usize>>>, MazeError> {
+        if k == 0 {
+            return Ok(vec![]);
+        }
+
+        let shortest_path = Solver::dijkstra_path_finding_solver(maze, start, finish)?;
+        let mut paths: Vec<Vec<Pair<usize>>> = vec![shortest_path];
+        let mut candidates: BTreeSet<(usize, Vec<Pair<usize>>)> = BTreeSet::new();
+
+        while paths.len() < k {
+            let previous_path = &paths[paths.len() - 1];
+
+            // Deviate from the previous path at every cell in turn.
+            for i in 0..previous_path.len() - 1 {
+                let spur_coord = previous_path[i];
+                let root_path = &previous_path[..=i];
+
+                // Passages leaving the spur cell that a known path with the same root already uses.
+                let blocked_passages: HashSet<(Pair<usize>, Pair<usize>)> = paths
+                    .iter()
+                    .filter(|path| path.len() > i + 1 && &path[..=i] == root_path)
+                    .map(|path| (path[i], path[i + 1]))
+                    .collect();
+                let blocked_cells: HashSet<Pair<usize>> = root_path[..i].iter().copied().collect();
+
+                if let Some(spur_path) = Solver::restricted_shortest_path(
+                    maze,
+                    spur_coord,
+                    finish,
+                    &blocked_cells,
+                    &blocked_passages,
+                ) {
+                    let mut candidate_path = root_path[..i].to_vec();
+                    candidate_path.extend(spur_path);
+
+                    if !paths.contains(&candidate_path) {
+                        candidates.insert((candidate_path.len(), candidate_path));
+                    }
+                }
+            }
+
+            match candidates.pop_first() {
+                Some((_, path)) => paths.push(path),
+                None => break,
+            }
+        }
+
+        Ok(paths)
+    }
+
+    // Shortest walk from start to finish that passes every checkpoint. The order is exact
+    // (Held-Karp) up to `EXACT_CHECKPOINT_LIMIT` checkpoints; beyond that it is built nearest
+    // neighbour first and then improved with 2-opt.
+    #[allow(unused)]
+    pub fn checkpoint_path_finding_solver(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        checkpoints: &[Pair<usize>],
+    ) -> Result<CheckpointTour, MazeError> {
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let mut unique_checkpoints: Vec<Pair<usize>> = vec![];
+        for checkpoint in checkpoints {
+            maze.cell_at(*checkpoint)?;
+            if !unique_checkpoints.contains(checkpoint) {
+                unique_checkpoints.push(*checkpoint);
+            }
+        }
+
+        // Distance maps from the start (index 0) and from every checkpoint (index i + 1).
+        let mut distance_maps: Vec<Vec<Vec<i32>>> =
+            vec![Solver::build_distance_map(maze, start)?.1];
+        for checkpoint in &unique_checkpoints {
+            distance_maps.push(Solver::build_distance_map(maze, *checkpoint)?.1);
+        }
+
+        let points: Vec<Pair<usize>> = std::iter::once(start)
+            .chain(unique_checkpoints.iter().copied())
+            .chain(std::iter::once(finish))
+            .collect();
+        let finish_index = points.len() - 1;
+
+        // Pairwise distances between the points. The finish has no distance map of its own, but
+        // the maze is undirected, so its row is read off the others.
+        let mut distances: Vec<Vec<usize>> = vec![vec![0; points.len()]; points.len()];
+        for (i, distance_map) in distance_maps.iter().enumerate() {
+            for (j, point) in points.iter().enumerate() {
+                let distance = distance_map[point.y][point.x];
+                if distance == -1 {
+                    return Err(MazeError::NoPath {
+                        start: points[i],
+                        finish: *point,
+                    });
+                }
+
+                distances[i][j] = distance as usize;
+                distances[j][i] = distance as usize;
+            }
+        }
+
+        let order = if unique_checkpoints.len() <= EXACT_CHECKPOINT_LIMIT {
+            Solver::exact_checkpoint_order(&distances)
+        } else {
+            Solver::heuristic_checkpoint_order(&distances)
+        };
+
+        let mut path: Vec<Pair<usize>> = vec![start];
+        let mut length = 0;
+        let mut previous_index = 0;
+        for index in order.iter().copied().chain(std::iter::once(finish_index)) {
+            let leg = Solver::extract_path(
+                maze,
+                &distance_maps[previous_index],
+                points[previous_index],
+                points[index],
+                |_, _| 1,
+            )?;
+            path.extend(leg.into_iter().skip(1));
+            length += distances[previous_index][index];
+            previous_index = index;
+        }
+
+        Ok(CheckpointTour {
+            path,
+            checkpoints: order.iter().map(|index| points[*index]).collect(),
+            length,
+        })
+    }
+
+    // Breadth first search over (cell, keys held) states. Keys are picked up by walking onto their
+    // cell and open every door of their colour for the rest of the route.
+    #[allow(unused)]
+    pub fn key_door_path_finding_solver(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<KeyDoorRoute, MazeError> {
+        maze.cell_at(start)
+            .map_err(|_| MazeError::StartOutOfBounds(start))?;
+        maze.cell_at(finish)
+            .map_err(|_| MazeError::FinishOutOfBounds(finish))?;
+
+        let keys_at = |coord: Pair<usize>| -> u32 {
+            maze.keys
+                .iter()
+                .filter(|key| key.coord == coord)
+                .fold(0, |keys, key| keys | key.color.mask())
+        };
+
+        let start_state = (start, keys_at(start));
+        let mut previous_states: HashMap<(Pair<usize>, u32), (Pair<usize>, u32)> = HashMap::new();
+        let mut work_queue: VecDeque<(Pair<usize>, u32)> = VecDeque::from([start_state]);
+        let mut finish_state = None;
+
+        while let Some((current_coord, held_keys)) = work_queue.pop_front() {
+            if current_coord == finish {
+                finish_state = Some((current_coord, held_keys));
+                break;
+            }
+
+            for neighbour_coord in Solver::open_neighbours(maze, current_coord) {
+                if let Some(door) = maze.door_between(current_coord, neighbour_coord) {
+                    if held_keys & door.color.mask() == 0 {
+                        continue;
+                    }
+                }
+
+                let neighbour_state = (neighbour_coord, held_keys | keys_at(neighbour_coord));
+                if neighbour_state == start_state || previous_states.contains_key(&neighbour_state)
+                {
+                    continue;
+                }
+
+                previous_states.insert(neighbour_state, (current_coord, held_keys));
+                work_queue.push_back(neighbour_state);
+            }
+        }
+
+        let mut state = finish_state.ok_or(MazeError::NoPath { start, finish })?;
+        let mut path = vec![state.0];
+        while state != start_state {
+            state = previous_states[&state];
+            path.push(state.0);
+        }
+        path.reverse();
+
+        let mut key_pickups: Vec<Key> = vec![];
+        for coord in &path {
+            for key in maze.keys.iter().filter(|key| key.coord == *coord) {
+                if !key_pickups.contains(key) {
+                    key_pickups.push(*key);
+                }
+            }
+        }
+
+        Ok(KeyDoorRoute { path, key_pickups })
+    }
+
+    // Held-Karp over the checkpoints of a distance matrix whose first row is the start and last
+    // row is the finish. Returns the checkpoint indices in visiting order.
+    fn exact_checkpoint_order(distances: &[Vec<usize>]) -> Vec<usize> {
+        let checkpoint_count = distances.len() - 2;
+        let finish_index = distances.len() - 1;
+        if checkpoint_count == 0 {
+            return vec![];
+        }
+
+        // best[visited][last]: shortest walk from the start through the `visited` checkpoints,
+        // ending at checkpoint `last`.
+        let subset_count = 1 << checkpoint_count;
+        let mut best: Vec<Vec<usize>> = vec![vec![usize::MAX; checkpoint_count]; subset_count];
+        let mut previous: Vec<Vec<usize>> = vec![vec![0; checkpoint_count]; subset_count];
+        for last in 0..checkpoint_count {
+            best[1 << last][last] = distances[0][last + 1];
+        }
+
+        for visited in 1..subset_count {
+            for last in 0..checkpoint_count {
+                if visited & (1 << last) == 0 || best[visited][last] == usize::MAX {
+                    continue;
+                }
+
+                for next in 0..checkpoint_count {
+                    if visited & (1 << next) != 0 {
+                        continue;
+                    }
+
+                    let next_visited = visited | (1 << next);
+                    let distance = best[visited][last] + distances[last + 1][next + 1];
+                    if distance < best[next_visited][next] {
+                        best[next_visited][next] = distance;
+                        previous[next_visited][next] = last;
+                    }
+                }
+            }
+        }
+
+        let all_visited = subset_count - 1;
+        let mut last = (0..checkpoint_count)
+            .min_by_key(|last| best[all_visited][*last] + distances[*last + 1][finish_index])
+            .expect("Missing checkpoint");
+
+        let mut order = vec![];
+        let mut visited = all_visited;
+        loop {
+            order.push(last + 1);
+            let next_visited = visited & !(1 << last);
+            if next_visited == 0 {
+                break;
+            }
+
+            last = previous[visited][last];
+            visited = next_visited;
+        }
+        order.reverse();
+
+        order
+    }
+
+    // Nearest neighbour tour, improved by reversing stretches of it (2-opt) for as long as that
+    // makes it shorter. Same distance matrix layout as `exact_checkpoint_order`.
+    fn heuristic_checkpoint_order(distances: &[Vec<usize>]) -> Vec<usize> {
+        let finish_index = distances.len() - 1;
+
+        let mut tour = vec![0];
+        let mut remaining: Vec<usize> = (1..finish_index).collect();
+        while !remaining.is_empty() {
+            let last = tour[tour.len() - 1];
+            let (i, _) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, index)| distances[last][**index])
+                .expect("Missing checkpoint");
+            tour.push(remaining.swap_remove(i));
+        }
+        tour.push(finish_index);
+
+        // The start and the finish stay at the two ends.
+        let mut improved = true;
+        while improved {
+            improved = false;
+
+            for i in 1..tour.len() - 2 {
+                for j in i + 1..tour.len() - 1 {
+                    let current_length =
+                        distances[tour[i - 1]][tour[i]] + distances[tour[j]][tour[j + 1]];
+                    let reversed_length =
+                        distances[tour[i - 1]][tour[j]] + distances[tour[i]][tour[j + 1]];
+
+                    if reversed_length < current_length {
+                        tour[i..=j].reverse();
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        tour[1..tour.len() - 1].to_vec()
+    }
+
+    // Cells from `coord` back to the root of a parent direction map, `coord` first.
+    fn follow_parent_dirs(
+        parent_dirs: &[u8],
+        width: usize,
+        coord: Pair<usize>,
+    ) -> Vec<Pair<usize>> {
+        let mut path = vec![coord];
+        let mut current_coord = coord;
+
+        loop {
+            let dir = parent_dirs[current_coord.index(width)];
+            if dir == ROOT {
+                break;
+            }
+
+            let offset = NEIGHBOUR_MAP[dir as usize];
+            current_coord = Pair::new(
+                (current_coord.x as i32 + offset[0]) as usize,
+                (current_coord.y as i32 + offset[1]) as usize,
+            );
+            path.push(current_coord);
+        }
+
+        path
+    }
+
+    // Cells reachable in one step from `coord`.
+    pub fn open_neighbours(maze: &Maze, coord: Pair<usize>) -> Vec<Pair<usize>> {
+        let cell = &maze.cells[coord.index(maze.width)];
+
+        maze.neighbours(coord, CellReachType::Anything)
+            .into_iter()
+            .filter(|(dir, _)| !cell.paths[*dir])
+            .map(|(_, neighbour_coord)| neighbour_coord)
+            .collect()
+    }
+
+    // Breadth first search that avoids the given cells and never steps through the given
+    // passages.
+    fn restricted_shortest_path(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        blocked_cells: &HashSet<Pair<usize>>,
+        blocked_passages: &HashSet<(Pair<usize>, Pair<usize>)>,
+    ) -> Option<Vec<Pair<usize>>> {
+        let mut previous_coords: HashMap<Pair<usize>, Pair<usize>> = HashMap::new();
+        let mut work_queue: VecDeque<Pair<usize>> = VecDeque::from([start]);
+
+        while let Some(current_coord) = work_queue.pop_front() {
+            if current_coord == finish {
+                let mut path = vec![finish];
+                let mut coord = finish;
+                while coord != start {
+                    coord = previous_coords[&coord];
+                    path.push(coord);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            for neighbour_coord in Solver::open_neighbours(maze, current_coord) {
+                if neighbour_coord == start
+                    || blocked_cells.contains(&neighbour_coord)
+                    || blocked_passages.contains(&(current_coord, neighbour_coord))
+                    || previous_coords.contains_key(&neighbour_coord)
+                {
+                    continue;
+                }
+
+                previous_coords.insert(neighbour_coord, current_coord);
+                work_queue.push_back(neighbour_coord);
+            }
+        }
+
+        None
+    }
+
+    // Reached cell with the highest distance among the ones accepted by `filter`. Falls back to
+    // (0, 0) when no cell qualifies.
+    fn farthest_cell(
+        distance_map: &[Vec<i32>],
+        filter: impl Fn(Pair<usize>) -> bool,
+    ) -> Pair<usize> {
+        let mut farthest_coord = Pair::new(0, 0);
+        let mut farthest_distance = -1;
+
+        for (y, row) in distance_map.iter().enumerate() {
+            for (x, distance) in row.iter().enumerate() {
+                if *distance > farthest_distance && filter(Pair::new(x, y)) {
+                    farthest_distance = *distance;
+                    farthest_coord = Pair::new(x, y);
+                }
+            }
+        }
+
+        farthest_coord
+    }
+
+    // Unlike with breadth first search, a cell can first be reached through a more expensive route,
+    // so its cost is only final once the cell is popped from the queue.
+    fn build_weighted_distance_map(
+        maze: &Maze,
+        start: Pair<usize>,
+        finish: Option<Pair<usize>>,
+    ) -> Result<Vec<Vec<i32>>, MazeError> {
+        let mut cost_map: Vec<Vec<i32>> = vec![vec![-1; maze.width]; maze.height];
+        let mut settled_map: Vec<Vec<bool>> = vec![vec![false; maze.width]; maze.height];
+
+        let mut work_queue: BinaryHeap<Reverse<(i32, Pair<usize>)>> = BinaryHeap::new();
+        work_queue.push(Reverse((0, start)));
+        cost_map[start.y][start.x] = 0;
+
+        while let Some(Reverse((current_cost, current_coord))) = work_queue.pop() {
+            if settled_map[current_coord.y][current_coord.x] {
+                // Stale queue entry.
+                continue;
+            }
+            settled_map[current_coord.y][current_coord.x] = true;
+
+            if Some(current_coord) == finish {
+                break;
+            }
+
+            for (dir, offset) in NEIGHBOUR_MAP.iter().enumerate() {
+                let neighbour_coord = Pair::new(
+                    current_coord.x as i32 + offset[0],
+                    current_coord.y as i32 + offset[1],
+                );
+
+                if neighbour_coord.x < 0
+                    || neighbour_coord.y < 0
+                    || neighbour_coord.x >= maze.width as i32
+                    || neighbour_coord.y >= maze.height as i32
+                {
+                    continue;
+                }
+
+                if maze.cell_at(current_coord)?.paths[dir] {
+                    // It's a wall.
+                    continue;
+                }
+
+                let neighbour_coord = neighbour_coord.to_usize();
+                if maze.cell_at(neighbour_coord)?.cost == 0 {
+                    return Err(MazeError::InvalidCost(neighbour_coord));
+                }
+                let neighbour_cost = Solver::entry_cost(maze, neighbour_coord, (dir + 2) % 4)
+                    .and_then(|step_cost| current_cost.checked_add(step_cost))
+                    .ok_or(MazeError::CostOverflow(neighbour_coord))?;
+                let known_cost = cost_map[neighbour_coord.y][neighbour_coord.x];
+                if known_cost != -1 && known_cost <= neighbour_cost {
+                    continue;
+                }
+
+                cost_map[neighbour_coord.y][neighbour_coord.x] = neighbour_cost;
+                work_queue.push(Reverse((neighbour_cost, neighbour_coord)));
+            }
+        }
+
+        Ok(cost_map)
+    }
+
+    // Cost of entering the cell at `coord` through its side `dir`, `None` if it does not fit into
+    // an `i32`.
+    fn entry_cost(maze: &Maze, coord: Pair<usize>, dir: usize) -> Option<i32> {
+        maze.cells[coord.index(maze.width)]
+            .cost
+            .checked_add(maze.passage_cost(coord, dir))
+            .and_then(|cost| i32::try_from(cost).ok())
+    }
+
+    // Walks back from `finish` along decreasing distances. Every reached cell except `start` has
+    // an open neighbour exactly the cost of the step in between closer, as long as the distances
+    // are final. `step_cost` is the cost of entering a cell from its side `dir`.
+    fn extract_path(
+        maze: &Maze,
+        distance_map: &[Vec<i32>],
+        start: Pair<usize>,
+        finish: Pair<usize>,
+        step_cost: impl Fn(Pair<usize>, usize) -> i32,
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        let mut current_distance = distance_map[finish.y][finish.x];
+        if current_distance == -1 {
+            return Err(MazeError::NoPath { start, finish });
+        }
+
+        let mut path: Vec<Pair<usize>> = vec![];
+        let mut current_coord = finish;
+
+        path.push(current_coord);
+
+        while current_coord != start {
+            let current_cell = maze.cell_at(current_coord)?;
+
+            let (previous_coord, previous_distance) = NEIGHBOUR_MAP
+                .iter()
+                .enumerate()
+                .filter(|(dir, _)| !current_cell.paths[*dir])
+                .map(|(dir, offset)| {
+                    let coord = Pair::new(
+                        current_coord.x as i32 + offset[0],
+                        current_coord.y as i32 + offset[1],
+                    );
+                    (dir, coord)
+                })
+                .filter(|(_, coord)| {
+                    coord.x >= 0
+                        && coord.y >= 0
+                        && coord.x < maze.width as i32
+                        && coord.y < maze.height as i32
+                })
+                .map(|(dir, coord)| {
+                    (
+                        coord.to_usize(),
+                        current_distance - step_cost(current_coord, dir),
+                    )
+                })
+                .find(|(coord, previous_distance)| {
+                    *previous_distance >= 0
+                        && *previous_distance < current_distance
+                        && distance_map[coord.y][coord.x] == *previous_distance
+                })
+                .ok_or(MazeError::InconsistentWalls(current_coord))?;
+
+            current_distance = previous_distance;
+            current_coord = previous_coord;
+            path.push(current_coord);
+        }
+
+        path.reverse();
+
+        Ok(path)
+    }
+
+    fn extract_circle_maze_path(
+        maze: &CircleMaze,
+        distance_map: &[Vec<i32>],
+        start: Pair<usize>,
+        finish: Pair<usize>,
+    ) -> Result<Vec<Pair<usize>>, MazeError> {
+        let mut current_distance = distance_map[finish.y][finish.x];
+        if current_distance == -1 {
+            return Err(MazeError::NoPath { start, finish });
+        }
+
+        let mut path: Vec<Pair<usize>> = vec![];
+        let mut current_coord = finish;
+
+        path.push(current_coord);
+
+        while current_coord != start {
+            let current_cell = maze.cell_at(current_coord)?;
+
+            let previous_coord = maze
+                .neighbours(current_coord, CellReachType::ReachableOnly)
+                .into_iter()
+                .find(|(dir, coord)| {
+                    current_cell.is_open_at(*dir)
+                        && distance_map[coord.y][coord.x] == current_distance - 1
+                })
+                .map(|(_, coord)| coord)
+                .ok_or(MazeError::InconsistentWalls(current_coord))?;
+
+            current_distance -= 1;
+            current_coord = previous_coord;
+            path.push(current_coord);
+        }
+
+        path.reverse();
+
+        Ok(path)
+    }
+
+    fn circle_maze_max_step_length(maze: &CircleMaze) -> f32 {
+        let mut max_step_length: f32 = 1.0;
+
+        for y in 0..maze.height {
+            for x in 0..maze.cells[y].len() {
+                let coord = Pair::new(x, y);
+                let (x_from, y_from) = maze.cell_position(coord, 1.0);
+
+                for neighbour_coord in maze.neighbours(coord, CellReachType::Anything).values() {
+                    let (x_to, y_to) = maze.cell_position(*neighbour_coord, 1.0);
+                    max_step_length = max_step_length.max((x_to - x_from).hypot(y_to - y_from));
+                }
+            }
+        }
+
+        max_step_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_door::KeyColor;
+    use crate::maze_builder::MazeBuilder;
+
+    // Two cells side by side. The passage is open from the west cell only.
+    fn one_sided_maze() -> Maze {
+        let mut maze = Maze::new_full(2, 1);
+        maze.cells[0].paths[EAST] = false;
+
+        maze
+    }
+
+    #[test]
+    fn reports_missing_paths_between_disconnected_cells() {
+        let maze = Maze::new_full(2, 2);
+        let start = Pair::new(0, 0);
+        let finish = Pair::new(1, 1);
+
+        assert_eq!(
+            Solver::dijkstra_path_finding_solver(&maze, start, finish),
+            Err(MazeError::NoPath { start, finish })
+        );
+        assert_eq!(
+            Solver::a_star_path_finding_solver(&maze, start, finish, Heuristic::Manhattan)
+                .map(|(path, _)| path),
+            Err(MazeError::NoPath { start, finish })
+        );
+        assert_eq!(
+            Solver::weighted_dijkstra_path_finding_solver(&maze, start, finish)
+                .map(|(_, path)| path),
+            Err(MazeError::NoPath { start, finish })
+        );
+    }
+
+    #[test]
+    fn reports_start_and_finish_out_of_bounds() {
+        let maze = Maze::new_empty(3, 3);
+        let inside = Pair::new(1, 1);
+        let outside = Pair::new(3, 0);
+
+        assert_eq!(
+            Solver::build_distance_map(&maze, outside).map(|(max, _)| max),
+            Err(MazeError::StartOutOfBounds(outside))
+        );
+        assert_eq!(
+            Solver::dijkstra_path_finding_solver(&maze, outside, inside),
+            Err(MazeError::StartOutOfBounds(outside))
+        );
+        assert_eq!(
+            Solver::dijkstra_path_finding_solver(&maze, inside, outside),
+            Err(MazeError::FinishOutOfBounds(outside))
+        );
+    }
+
+    #[test]
+    fn reports_cells_out_of_bounds() {
+        let mut maze = Maze::new_full(3, 2);
+        let outside = Pair::new(0, 2);
+
+        assert_eq!(
+            maze.cell_at(outside).map(|cell| cell.cost),
+            Err(MazeError::OutOfBounds(outside))
+        );
+        assert_eq!(
+            MazeBuilder::wilson_maze_creation(&mut maze, outside),
+            Err(MazeError::StartOutOfBounds(outside))
+        );
+    }
+
+    #[test]
+    fn follows_one_sided_passages_only_the_open_way() {
+        let maze = one_sided_maze();
+        let west = Pair::new(0, 0);
+        let east = Pair::new(1, 0);
+
+        assert_eq!(
+            Solver::dijkstra_path_finding_solver(&maze, west, east),
+            Err(MazeError::InconsistentWalls(east))
+        );
+        assert_eq!(
+            Solver::dijkstra_path_finding_solver(&maze, east, west),
+            Err(MazeError::NoPath {
+                start: east,
+                finish: west
+            })
+        );
+    }
+
+    #[test]
+    fn takes_cheaper_detours_around_expensive_cells() {
+        let mut maze = Maze::new_empty(3, 2);
+        maze.cells[1].cost = 10;
+        let start = Pair::new(0, 0);
+        let finish = Pair::new(2, 0);
+
+        let (cost, path) =
+            Solver::weighted_dijkstra_path_finding_solver(&maze, start, finish).unwrap();
+
+        assert_eq!(cost, 4);
+        assert_eq!(
+            path,
+            [(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]
+                .iter()
+                .map(|(x, y)| Pair::new(*x, *y))
+                .collect::<Vec<_>>()
+        );
+
+        // A toll on the detour makes the expensive cell the cheaper way again.
+        maze.set_passage_cost(Pair::new(1, 1), EAST, 20).unwrap();
+        let (cost, path) =
+            Solver::weighted_dijkstra_path_finding_solver(&maze, start, finish).unwrap();
+
+        assert_eq!(cost, 11);
+        assert_eq!(path, vec![start, Pair::new(1, 0), finish]);
+    }
+
+    #[test]
+    fn builds_cost_maps() {
+        // Open floor except for a wall between the two eastern cells of the top row.
+        let mut maze = Maze::new_full(3, 2);
+        maze.connect_cells(0, 0, EAST);
+        maze.connect_cells(0, 0, SOUTH);
+        maze.connect_cells(1, 0, SOUTH);
+        maze.connect_cells(0, 1, EAST);
+        maze.connect_cells(1, 1, EAST);
+        maze.connect_cells(2, 1, NORTH);
+        maze.cell_at_mut(Pair::new(1, 1)).unwrap().cost = 5;
+
+        let (max_cost, cost_map) = Solver::build_cost_map(&maze, Pair::new(0, 0)).unwrap();
+
+        assert_eq!(max_cost, 8);
+        assert_eq!(cost_map, vec![vec![0, 1, 8], vec![1, 6, 7]]);
+    }
+
+    #[test]
+    fn rejects_free_cells() {
+        let mut maze = Maze::new_empty(2, 2);
+        maze.cell_at_mut(Pair::new(1, 0)).unwrap().cost = 0;
+
+        assert_eq!(
+            Solver::build_cost_map(&maze, Pair::new(0, 0)).map(|(max_cost, _)| max_cost),
+            Err(MazeError::InvalidCost(Pair::new(1, 0)))
+        );
+    }
+
+    #[test]
+    fn reports_costs_too_large_to_add_up() {
+        let mut maze = Maze::new_empty(3, 1);
+        maze.cells[1].cost = i32::MAX as u32;
+        maze.cells[2].cost = 1;
+
+        assert_eq!(
+            Solver::build_cost_map(&maze, Pair::new(0, 0)).map(|(max_cost, _)| max_cost),
+            Err(MazeError::CostOverflow(Pair::new(2, 0)))
+        );
+
+        maze.cells[1].cost = 1;
+        maze.set_passage_cost(Pair::new(1, 0), EAST, u32::MAX)
+            .unwrap();
+
+        assert_eq!(
+            Solver::build_cost_map(&maze, Pair::new(0, 0)).map(|(max_cost, _)| max_cost),
+            Err(MazeError::CostOverflow(Pair::new(2, 0)))
+        );
+    }
+
+    // Every step goes to a neighbour through an open side.
+    fn assert_walkable(maze: &Maze, path: &[Pair<usize>]) {
+        for step in path.windows(2) {
+            let cell = &maze.cells[step[0].index(maze.width)];
+            assert!(maze
+                .neighbours(step[0], CellReachType::Anything)
+                .into_iter()
+                .any(|(dir, coord)| coord == step[1] && !cell.paths[dir]));
+        }
+    }
+
+    fn seeded_maze(width: usize, height: usize, seed: u64) -> Maze {
+        let mut maze = Maze::new_full(width, height);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, seed).unwrap();
+
+        maze
+    }
+
+    fn distance(maze: &Maze, from: Pair<usize>, to: Pair<usize>) -> i32 {
+        let (_, distance_map) = Solver::build_distance_map(maze, from).unwrap();
+
+        distance_map[to.y][to.x]
+    }
+
+    // Longest shortest path between any two cells passing `filter`, by trying every start.
+    fn diameter(maze: &Maze, filter: impl Fn(Pair<usize>) -> bool) -> i32 {
+        let mut diameter = 0;
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                if !filter(Pair::new(x, y)) {
+                    continue;
+                }
+
+                let (_, distance_map) = Solver::build_distance_map(maze, Pair::new(x, y)).unwrap();
+                for (to_y, row) in distance_map.iter().enumerate() {
+                    for (to_x, distance) in row.iter().enumerate() {
+                        if filter(Pair::new(to_x, to_y)) {
+                            diameter = diameter.max(*distance);
+                        }
+                    }
+                }
+            }
+        }
+
+        diameter
+    }
+
+    #[test]
+    fn finds_the_ends_of_a_corridor() {
+        // Right along the top row and back along the bottom one.
+        let mut maze = Maze::new_full(5, 2);
+        for x in 0..4 {
+            maze.connect_cells(x, 0, EAST);
+            maze.connect_cells(x, 1, EAST);
+        }
+        maze.connect_cells(4, 0, SOUTH);
+
+        let (start, finish) = Solver::longest_path_endpoints(&maze).unwrap();
+        let mut ends = [start, finish];
+        ends.sort_by_key(|coord| coord.y);
+
+        assert_eq!(ends, [Pair::new(0, 0), Pair::new(0, 1)]);
+    }
+
+    #[test]
+    fn finds_the_longest_path_of_perfect_mazes() {
+        for seed in 0..5 {
+            let maze = seeded_maze(9, 7, seed);
+            let (start, finish) = Solver::longest_path_endpoints(&maze).unwrap();
+
+            assert_eq!(distance(&maze, start, finish), diameter(&maze, |_| true));
+        }
+    }
+
+    #[test]
+    fn finds_the_longest_path_between_boundary_cells() {
+        for seed in 0..5 {
+            let maze = seeded_maze(9, 7, seed);
+            let is_on_boundary =
+                |coord: Pair<usize>| coord.x == 0 || coord.y == 0 || coord.x == 8 || coord.y == 6;
+            let (start, finish) = Solver::longest_boundary_path_endpoints(&maze).unwrap();
+
+            assert!(is_on_boundary(start) && is_on_boundary(finish));
+            assert_eq!(
+                distance(&maze, start, finish),
+                diameter(&maze, is_on_boundary)
+            );
+        }
+    }
+
+    #[test]
+    fn finds_the_longest_path_of_circle_mazes() {
+        let mut maze = CircleMaze::new(5);
+        MazeBuilder::random_circle_maze_creation(&mut maze, Pair::new(0, 0));
+
+        let (start, finish) = Solver::longest_path_endpoints_for_circle_maze(&maze).unwrap();
+        let (_, distance_map) = Solver::build_distance_map_for_circle_maze(&maze, start).unwrap();
+
+        let mut diameter = 0;
+        for y in 0..maze.height {
+            for x in 0..maze.cells[y].len() {
+                let (max_distance, _) =
+                    Solver::build_distance_map_for_circle_maze(&maze, Pair::new(x, y)).unwrap();
+                diameter = diameter.max(max_distance);
+            }
+        }
+
+        assert_eq!(distance_map[finish.y][finish.x], diameter);
+    }
+
+    #[test]
+    fn counts_the_single_path_of_perfect_mazes() {
+        let maze = seeded_maze(9, 7, 11);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(8, 6));
+
+        assert_eq!(Solver::count_simple_paths(&maze, start, finish, 100), Ok(1));
+        assert_eq!(Solver::count_shortest_paths(&maze, start, finish), Ok(1));
+        assert_eq!(
+            Solver::k_shortest_paths(&maze, start, finish, 5),
+            Ok(vec![Solver::dijkstra_path_finding_solver(
+                &maze, start, finish
+            )
+            .unwrap()])
+        );
+    }
+
+    #[test]
+    fn counts_paths_across_open_floors() {
+        let maze = Maze::new_empty(2, 2);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(1, 1));
+        assert_eq!(Solver::count_simple_paths(&maze, start, finish, 100), Ok(2));
+        assert_eq!(Solver::count_shortest_paths(&maze, start, finish), Ok(2));
+
+        let maze = Maze::new_empty(3, 3);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(2, 2));
+        assert_eq!(
+            Solver::count_simple_paths(&maze, start, finish, 100),
+            Ok(12)
+        );
+        assert_eq!(Solver::count_simple_paths(&maze, start, finish, 5), Ok(5));
+        assert_eq!(Solver::count_shortest_paths(&maze, start, finish), Ok(6));
+    }
+
+    #[test]
+    fn lists_every_simple_path_shortest_first() {
+        let maze = Maze::new_empty(3, 3);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(2, 2));
+
+        let paths = Solver::k_shortest_paths(&maze, start, finish, 20).unwrap();
+
+        assert_eq!(paths.len(), 12);
+        assert_eq!(paths.iter().filter(|path| path.len() == 5).count(), 6);
+        assert!(paths.windows(2).all(|pair| pair[0].len() <= pair[1].len()));
+        assert_eq!(paths.iter().collect::<HashSet<_>>().len(), paths.len());
+        for path in &paths {
+            assert_eq!((path[0], path[path.len() - 1]), (start, finish));
+            assert_eq!(path.iter().collect::<HashSet<_>>().len(), path.len());
+            assert_walkable(&maze, path);
+        }
+
+        // Ties can come in any order, but never after a longer path.
+        let paths = Solver::k_shortest_paths(&maze, start, finish, 3).unwrap();
+        assert_eq!(paths.len(), 3);
+        assert!(paths.iter().all(|path| path.len() == 5));
+        assert_eq!(
+            Solver::k_shortest_paths(&maze, start, finish, 0),
+            Ok(vec![])
+        );
+    }
+
+    // A seeded maze with a few extra passages, so there is more than one way around.
+    fn looped_maze(seed: u64) -> Maze {
+        let mut maze = seeded_maze(9, 7, seed);
+        for (x, y) in [(1, 1), (4, 2), (6, 5), (2, 4)] {
+            maze.connect_cells(x, y, EAST);
+            maze.connect_cells(x, y, SOUTH);
+        }
+
+        maze
+    }
+
+    // Shortest walk from `from` through every point of `remaining`, in the best order, to `to`.
+    fn brute_force_tour_length(
+        maze: &Maze,
+        from: Pair<usize>,
+        remaining: &[Pair<usize>],
+        to: Pair<usize>,
+    ) -> usize {
+        if remaining.is_empty() {
+            return distance(maze, from, to) as usize;
+        }
+
+        (0..remaining.len())
+            .map(|i| {
+                let mut rest = remaining.to_vec();
+                let next = rest.remove(i);
+                distance(maze, from, next) as usize + brute_force_tour_length(maze, next, &rest, to)
+            })
+            .min()
+            .unwrap()
+    }
+
+    fn assert_tour(maze: &Maze, tour: &CheckpointTour, start: Pair<usize>, finish: Pair<usize>) {
+        assert_eq!(
+            (tour.path[0], tour.path[tour.path.len() - 1]),
+            (start, finish)
+        );
+        assert_eq!(tour.path.len(), tour.length + 1);
+        assert_walkable(maze, &tour.path);
+
+        // Checkpoints come up along the path in the order listed.
+        let mut path_index = 0;
+        for checkpoint in &tour.checkpoints {
+            path_index += tour.path[path_index..]
+                .iter()
+                .position(|coord| coord == checkpoint)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn orders_few_checkpoints_exactly() {
+        let (start, finish) = (Pair::new(0, 0), Pair::new(8, 6));
+        let checkpoints = [(7, 0), (1, 5), (4, 3), (8, 2), (2, 2), (5, 6)]
+            .iter()
+            .map(|(x, y)| Pair::new(*x, *y))
+            .collect::<Vec<_>>();
+
+        for seed in 0..3 {
+            let maze = looped_maze(seed);
+            let tour =
+                Solver::checkpoint_path_finding_solver(&maze, start, finish, &checkpoints).unwrap();
+
+            assert_tour(&maze, &tour, start, finish);
+            assert_eq!(
+                tour.checkpoints.iter().collect::<HashSet<_>>(),
+                checkpoints.iter().collect::<HashSet<_>>()
+            );
+            assert_eq!(
+                tour.length,
+                brute_force_tour_length(&maze, start, &checkpoints, finish)
+            );
+        }
+    }
+
+    #[test]
+    fn visits_every_checkpoint_of_large_sets() {
+        let maze = looped_maze(5);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(8, 6));
+        // Every third cell, well above `EXACT_CHECKPOINT_LIMIT`.
+        let checkpoints = (0..9 * 7)
+            .step_by(3)
+            .map(|i| Pair::new(i % 9, i / 9))
+            .collect::<Vec<_>>();
+        assert!(checkpoints.len() > EXACT_CHECKPOINT_LIMIT);
+
+        let tour =
+            Solver::checkpoint_path_finding_solver(&maze, start, finish, &checkpoints).unwrap();
+
+        assert_tour(&maze, &tour, start, finish);
+        assert_eq!(tour.checkpoints.len(), checkpoints.len());
+        for checkpoint in &checkpoints {
+            assert!(tour.path.contains(checkpoint));
+        }
+    }
+
+    #[test]
+    fn reports_unreachable_checkpoints() {
+        let mut maze = Maze::new_full(3, 1);
+        maze.connect_cells(0, 0, EAST);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(1, 0));
+
+        assert_eq!(
+            Solver::checkpoint_path_finding_solver(&maze, start, finish, &[Pair::new(2, 0)])
+                .map(|tour| tour.length),
+            Err(MazeError::NoPath {
+                start,
+                finish: Pair::new(2, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn fetches_keys_from_dead_ends() {
+        // A corridor along the top row locked before its last cell, with the key down a dead end.
+        let mut maze = Maze::new_full(4, 2);
+        for x in 0..3 {
+            maze.connect_cells(x, 0, EAST);
+        }
+        maze.connect_cells(1, 0, SOUTH);
+        maze.add_door(KeyColor::Red, Pair::new(2, 0), EAST).unwrap();
+        maze.add_key(KeyColor::Red, Pair::new(1, 1)).unwrap();
+        let (start, finish) = (Pair::new(0, 0), Pair::new(3, 0));
+
+        let route = Solver::key_door_path_finding_solver(&maze, start, finish).unwrap();
+
+        assert_eq!(
+            route.path,
+            [(0, 0), (1, 0), (1, 1), (1, 0), (2, 0), (3, 0)]
+                .iter()
+                .map(|(x, y)| Pair::new(*x, *y))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(route.key_pickups, maze.keys);
+
+        maze.keys.clear();
+        assert_eq!(
+            Solver::key_door_path_finding_solver(&maze, start, finish).map(|route| route.path),
+            Err(MazeError::NoPath { start, finish })
+        );
+    }
+
+    #[test]
+    fn searches_from_both_ends_as_far_as_dijkstra() {
+        for seed in 0..10 {
+            let maze = if seed % 2 == 0 {
+                seeded_maze(9, 7, seed)
+            } else {
+                looped_maze(seed)
+            };
+
+            for (start, finish) in [
+                ((0, 0), (8, 6)),
+                ((4, 3), (0, 6)),
+                ((8, 0), (7, 1)),
+                ((2, 5), (2, 5)),
+            ] {
+                let (start, finish) = (Pair::new(start.0, start.1), Pair::new(finish.0, finish.1));
+                let (path, _) =
+                    Solver::bidirectional_path_finding_solver(&maze, start, finish).unwrap();
+                let solution = Solver::dijkstra_path_finding_solver(&maze, start, finish).unwrap();
+
+                assert_eq!((path[0], path[path.len() - 1]), (start, finish));
+                assert_eq!(path.len(), solution.len());
+                assert_walkable(&maze, &path);
+            }
+        }
+    }
+
+    #[test]
+    fn searches_from_both_ends_of_disconnected_mazes() {
+        let mut maze = Maze::new_full(3, 3);
+        maze.connect_cells(0, 0, EAST);
+        maze.connect_cells(2, 2, NORTH);
+        let (start, finish) = (Pair::new(0, 0), Pair::new(2, 2));
 
-        return path;
+        assert_eq!(
+            Solver::bidirectional_path_finding_solver(&maze, start, finish).map(|(path, _)| path),
+            Err(MazeError::NoPath { start, finish })
+        );
+        assert_eq!(
+            Solver::bidirectional_path_finding_solver(&maze, start, start),
+            Ok((vec![start], 0))
+        );
     }
 }
@@ -1,103 +1,585 @@
+use std::collections::HashMap;
+use std::{fmt, fs, io};
+
 use draw::{
-    render, shape::LinePoint, Canvas, Color, Drawing, Point, Shape, Style, SvgRenderer, RGB,
+    render::Renderer, shape::LinePoint, Canvas, Drawing, Fill, Point, Shape, Stroke, Style,
+    SvgRenderer, RGB,
 };
 
-use crate::{Maze, Pair};
+use svg::node::element::{path::Data, Circle, Path, Rectangle};
+use svg::Document;
+
+use crate::{
+    agent_solver::ExplorationTrace,
+    circle_maze::{polar_point, CircleMaze, CircleWall},
+    glyph::number_strokes,
+    solver::CheckpointTour,
+    theme::{Rgb, Theme},
+    util::{Coord, NORTH},
+    Maze, Pair,
+};
+
+// A finished drawing, ready to be saved, streamed or kept in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgImage {
+    content: String,
+}
+
+impl SvgImage {
+    fn from_canvas(canvas: &Canvas) -> SvgImage {
+        SvgImage {
+            content: String::from_utf8(SvgRenderer::new().render(canvas))
+                .expect("SVG is not valid UTF-8"),
+        }
+    }
+
+    // For drawings built directly with the `svg` crate.
+    pub fn from_document(document: &Document) -> SvgImage {
+        SvgImage {
+            content: document.to_string(),
+        }
+    }
+
+    #[allow(unused)]
+    pub fn as_str(&self) -> &str {
+        &self.content
+    }
+
+    #[allow(unused)]
+    pub fn into_string(self) -> String {
+        self.content
+    }
+
+    #[allow(unused)]
+    pub fn write_to(&self, output: &mut impl io::Write) -> io::Result<()> {
+        output.write_all(self.content.as_bytes())
+    }
+
+    // Overwrites whatever is at `path`.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        fs::write(path, &self.content)
+    }
+}
+
+impl fmt::Display for SvgImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.content)
+    }
+}
 
 pub struct SvgDrawer;
 
 impl SvgDrawer {
     #[allow(unused)]
-    pub fn draw(maze: &Maze, cell_size: u32, wall_thickness: u32, solution: Vec<Pair<usize>>) {
-        let cell_size_f32 = cell_size as f32;
-        let w: u32 = cell_size * maze.width as u32;
-        let h: u32 = cell_size * maze.height as u32;
-        let mut canvas: Canvas = Canvas::new(w, h);
+    pub fn draw(
+        maze: &Maze,
+        cell_size: u32,
+        wall_thickness: u32,
+        solution: Vec<Pair<usize>>,
+    ) -> io::Result<()> {
+        let theme = SvgDrawer::sized_theme(cell_size, wall_thickness);
+
+        SvgDrawer::render(maze, &solution, None, &theme).save("./mazey.svg")
+    }
+
+    // `distance_map` is what `Solver::build_distance_map` returns.
+    #[allow(unused)]
+    pub fn render(
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        distance_map: Option<(i32, &[Vec<i32>])>,
+        theme: &Theme,
+    ) -> SvgImage {
+        let mut canvas = SvgDrawer::new_canvas(maze, theme);
+
+        if let Some((max_distance, distance_map)) = distance_map {
+            for (y, row) in distance_map.iter().enumerate() {
+                for (x, distance) in row.iter().enumerate() {
+                    if let Some(color) = theme.heat_color(max_distance, *distance) {
+                        SvgDrawer::fill_cell(&mut canvas, Pair::new(x, y), color, theme);
+                    }
+                }
+            }
+        }
+
+        SvgDrawer::draw_walls(&mut canvas, maze, theme);
+        SvgDrawer::draw_keys_and_doors(&mut canvas, maze, theme);
+        SvgDrawer::draw_solution(&mut canvas, solution, theme);
+
+        SvgImage::from_canvas(&canvas)
+    }
+
+    #[allow(unused)]
+    pub fn draw_exploration(
+        maze: &Maze,
+        cell_size: u32,
+        wall_thickness: u32,
+        trace: &ExplorationTrace,
+    ) -> io::Result<()> {
+        let theme = SvgDrawer::sized_theme(cell_size, wall_thickness);
+
+        SvgDrawer::render_exploration(maze, trace, &theme).save("./mazey.svg")
+    }
+
+    // Shades every cell an agent walked through along the heat map, further the more often it was
+    // there, and draws the route it settled on.
+    #[allow(unused)]
+    pub fn render_exploration(maze: &Maze, trace: &ExplorationTrace, theme: &Theme) -> SvgImage {
+        let mut canvas = SvgDrawer::new_canvas(maze, theme);
+
+        let mut visit_counts: HashMap<Pair<usize>, i32> = HashMap::new();
+        for coord in &trace.steps {
+            *visit_counts.entry(*coord).or_insert(0) += 1;
+        }
+        let max_visit_count = visit_counts.values().copied().max().unwrap_or(1);
+
+        for (coord, visit_count) in visit_counts {
+            if let Some(color) = theme.heat_color(max_visit_count - 1, visit_count - 1) {
+                SvgDrawer::fill_cell(&mut canvas, coord, color, theme);
+            }
+        }
+
+        SvgDrawer::draw_walls(&mut canvas, maze, theme);
+        SvgDrawer::draw_keys_and_doors(&mut canvas, maze, theme);
+        SvgDrawer::draw_solution(&mut canvas, &trace.path, theme);
+
+        SvgImage::from_canvas(&canvas)
+    }
+
+    #[allow(unused)]
+    pub fn draw_checkpoint_tour(
+        maze: &Maze,
+        cell_size: u32,
+        wall_thickness: u32,
+        tour: &CheckpointTour,
+    ) -> io::Result<()> {
+        let theme = SvgDrawer::sized_theme(cell_size, wall_thickness);
+
+        SvgDrawer::render_checkpoint_tour(maze, tour, &theme).save("./mazey.svg")
+    }
+
+    // Draws the tour with its checkpoints numbered in the order they are visited.
+    #[allow(unused)]
+    pub fn render_checkpoint_tour(maze: &Maze, tour: &CheckpointTour, theme: &Theme) -> SvgImage {
+        let mut canvas = SvgDrawer::new_canvas(maze, theme);
+
+        SvgDrawer::draw_walls(&mut canvas, maze, theme);
+        SvgDrawer::draw_keys_and_doors(&mut canvas, maze, theme);
+        SvgDrawer::draw_solution(&mut canvas, &tour.path, theme);
+
+        let label_thickness = stroke_width(theme.path_width / 2.0);
+        for (i, checkpoint) in tour.checkpoints.iter().enumerate() {
+            let (center_x, center_y) = cell_center(*checkpoint, theme);
+
+            canvas.display_list.add(
+                Drawing::new()
+                    .with_shape(Shape::Circle {
+                        radius: (theme.cell_size * 0.4) as u32,
+                    })
+                    .with_xy(center_x, center_y)
+                    .with_style(Style::new(
+                        Fill::new(rgb(theme.background.unwrap_or((255, 255, 255)))),
+                        Stroke::new(label_thickness, rgb(theme.path_color)),
+                    )),
+            );
+
+            for (from, to) in number_strokes(i + 1, center_x, center_y, theme.cell_size * 0.4) {
+                canvas.display_list.add(
+                    Drawing::new()
+                        .with_shape(Shape::Line {
+                            start: Point {
+                                x: from.0,
+                                y: from.1,
+                            },
+                            points: vec![LinePoint::Straight {
+                                point: Point { x: to.0, y: to.1 },
+                            }],
+                        })
+                        .with_style(Style::stroked(label_thickness, rgb(theme.wall_color))),
+                );
+            }
+        }
+
+        SvgImage::from_canvas(&canvas)
+    }
+
+    #[allow(unused)]
+    pub fn draw_circle_maze(
+        maze: &CircleMaze,
+        ring_height: u32,
+        wall_thickness: u32,
+        solution: Vec<Coord>,
+        distance_map: Option<(i32, Vec<Vec<i32>>)>,
+    ) -> io::Result<()> {
+        let theme = SvgDrawer::sized_theme(ring_height, wall_thickness);
 
+        SvgDrawer::render_circle_maze(
+            maze,
+            &solution,
+            distance_map
+                .as_ref()
+                .map(|(max_distance, distance_map)| (*max_distance, &distance_map[..])),
+            &theme,
+        )
+        .save("./mazey.svg")
+    }
+
+    // Ring walls are true arcs, so this one builds the SVG itself instead of going through a
+    // `Canvas`. Same layout as `FloDrawer::draw_circle_maze`, with the maze centre in the middle
+    // and `Theme::ring_height` as the ring height.
+    #[allow(unused)]
+    pub fn render_circle_maze(
+        maze: &CircleMaze,
+        solution: &[Coord],
+        distance_map: Option<(i32, &[Vec<i32>])>,
+        theme: &Theme,
+    ) -> SvgImage {
+        let ring_height = theme.ring_height();
+        let size = ring_height * (maze.height as f32 * 2.0 - 1.0) + theme.padding * 2.0;
+        let center = size / 2.0;
+        let point = |r: f32, alpha: f32| {
+            let (x, y) = polar_point(r, alpha);
+            (x + center, y + center)
+        };
+
+        let mut document = Document::new().set("viewBox", (0, 0, size, size));
+
+        if let Some(background) = theme.background {
+            document = document.add(
+                Rectangle::new()
+                    .set("width", size)
+                    .set("height", size)
+                    .set("fill", svg_color(background)),
+            );
+        }
+
+        // Heat map.
+        if let Some((max_distance, distance_map)) = distance_map {
+            for (y, row) in distance_map.iter().enumerate() {
+                let cell_arc = 360.0 / row.len() as f32;
+                let r_inner = (y as f32 - 0.5) * ring_height;
+                let r_outer = (y as f32 + 0.5) * ring_height;
+
+                for (x, distance) in row.iter().enumerate() {
+                    let fill = match theme.heat_color(max_distance, *distance) {
+                        Some(color) => svg_color(color),
+                        None => continue,
+                    };
+
+                    // The centre cell is a disc, every other cell a ring sector.
+                    if y == 0 {
+                        document = document.add(
+                            Circle::new()
+                                .set("cx", center)
+                                .set("cy", center)
+                                .set("r", r_outer)
+                                .set("fill", fill),
+                        );
+                        continue;
+                    }
+
+                    let alpha_from = cell_arc * x as f32;
+                    let alpha_to = cell_arc * (x + 1) as f32;
+                    let data = Data::new()
+                        .move_to(point(r_inner, alpha_from))
+                        .line_to(point(r_outer, alpha_from));
+                    let data = arc_to(data, r_outer, alpha_from, alpha_to, &point)
+                        .line_to(point(r_inner, alpha_to));
+                    let data = arc_to(data, r_inner, alpha_to, alpha_from, &point).close();
+
+                    document = document.add(Path::new().set("d", data).set("fill", fill));
+                }
+            }
+        }
+
+        // Walls.
+        let mut data = Data::new();
+        for wall in maze.wall_segments(ring_height) {
+            data = match wall {
+                CircleWall::Arc { radius, from, to } => {
+                    arc_to(data.move_to(point(radius, from)), radius, from, to, &point)
+                }
+                CircleWall::Radial {
+                    angle,
+                    inner_radius,
+                    outer_radius,
+                } => data
+                    .move_to(point(inner_radius, angle))
+                    .line_to(point(outer_radius, angle)),
+            };
+        }
+        document = document.add(
+            Path::new()
+                .set("d", data)
+                .set("fill", "none")
+                .set("stroke", svg_color(theme.wall_color))
+                .set("stroke-width", theme.wall_width)
+                .set("stroke-linecap", "round"),
+        );
+
+        // Solution.
+        let solution_points: Vec<(f32, f32)> = solution
+            .iter()
+            .map(|coord| {
+                let (x, y) = maze.cell_position(*coord, ring_height);
+                (x + center, y + center)
+            })
+            .collect();
+
+        if solution_points.len() > 1 {
+            let mut data = Data::new().move_to(solution_points[0]);
+            for solution_point in solution_points.iter().skip(1) {
+                data = data.line_to(*solution_point);
+            }
+
+            document = document.add(
+                Path::new()
+                    .set("d", data)
+                    .set("fill", "none")
+                    .set("stroke", svg_color(theme.path_color))
+                    .set("stroke-width", theme.path_width)
+                    .set("stroke-linecap", "round")
+                    .set("stroke-linejoin", "round"),
+            );
+        }
+
+        let markers = [
+            (solution_points.first(), theme.start_marker),
+            (solution_points.last(), theme.finish_marker),
+        ];
+        for (marker_point, color) in markers {
+            if let (Some((x, y)), Some(color)) = (marker_point, color) {
+                document = document.add(
+                    Circle::new()
+                        .set("cx", *x)
+                        .set("cy", *y)
+                        .set("r", ring_height * 0.3)
+                        .set("fill", svg_color(color)),
+                );
+            }
+        }
+
+        SvgImage::from_document(&document)
+    }
+
+    // The look these drawers had before themes: black walls and a red path on a transparent
+    // background, with nothing around the maze.
+    fn sized_theme(cell_size: u32, wall_thickness: u32) -> Theme {
+        Theme {
+            background: None,
+            cell_size: cell_size as f32,
+            wall_width: wall_thickness as f32,
+            path_width: wall_thickness as f32,
+            padding: 0.0,
+            start_marker: None,
+            finish_marker: None,
+            ..Theme::print()
+        }
+    }
+
+    fn new_canvas(maze: &Maze, theme: &Theme) -> Canvas {
+        let w = theme.cell_size * maze.width as f32 + theme.padding * 2.0;
+        let h = theme.cell_size * maze.height as f32 + theme.padding * 2.0;
+        let mut canvas = Canvas::new(w.ceil() as u32, h.ceil() as u32);
+
+        if let Some(background) = theme.background {
+            canvas.display_list.add(
+                Drawing::new()
+                    .with_shape(Shape::Rectangle {
+                        width: canvas.width,
+                        height: canvas.height,
+                    })
+                    .with_style(Style::filled(rgb(background))),
+            );
+        }
+
+        canvas
+    }
+
+    fn fill_cell(canvas: &mut Canvas, coord: Pair<usize>, color: Rgb, theme: &Theme) {
         canvas.display_list.add(
             Drawing::new()
                 .with_shape(Shape::Rectangle {
-                    width: w,
-                    height: h,
+                    width: theme.cell_size.ceil() as u32,
+                    height: theme.cell_size.ceil() as u32,
                 })
-                .with_style(Style::stroked(wall_thickness, Color::black())),
+                .with_xy(
+                    theme.padding + coord.x as f32 * theme.cell_size,
+                    theme.padding + coord.y as f32 * theme.cell_size,
+                )
+                .with_style(Style::filled(rgb(color))),
         );
+    }
 
-        let line_map = vec![
-            vec![0f32, 0f32, 1f32, 0f32],
-            vec![1f32, 0f32, 1f32, 1f32],
-            vec![0f32, 1f32, 1f32, 1f32],
-            vec![0f32, 0f32, 0f32, 1f32],
-        ];
+    fn draw_walls(canvas: &mut Canvas, maze: &Maze, theme: &Theme) {
+        for ((x1, y1), (x2, y2)) in maze.wall_segments(theme.cell_size) {
+            // Lines run a unit past both ends, so corners come out closed.
+            let (dx, dy) = if y1 == y2 { (1.0, 0.0) } else { (0.0, 1.0) };
 
-        let line_ending_adjustment = vec![
-            vec![-1f32, 0f32, 1f32, 0f32],
-            vec![0f32, -1f32, 0f32, 1f32],
-            vec![-1f32, 0f32, 1f32, 0f32],
-            vec![0f32, -1f32, 0f32, 1f32],
-        ];
+            canvas.display_list.add(
+                Drawing::new()
+                    .with_shape(Shape::Line {
+                        start: Point {
+                            x: theme.padding + x1 - dx,
+                            y: theme.padding + y1 - dy,
+                        },
+                        points: vec![LinePoint::Straight {
+                            point: Point {
+                                x: theme.padding + x2 + dx,
+                                y: theme.padding + y2 + dy,
+                            },
+                        }],
+                    })
+                    .with_style(Style::stroked(
+                        stroke_width(theme.wall_width),
+                        rgb(theme.wall_color),
+                    )),
+            );
+        }
+    }
 
-        for y in 0..maze.height {
-            for x in 0..maze.width {
-                let i = y * maze.width + x;
+    // Doors are drawn as a coloured bar across their passage, keys as a coloured dot.
+    fn draw_keys_and_doors(canvas: &mut Canvas, maze: &Maze, theme: &Theme) {
+        let cell_size = theme.cell_size;
 
-                let start_x: f32 = x as f32 * cell_size as f32;
-                let start_y: f32 = y as f32 * cell_size as f32;
+        for door in &maze.doors {
+            let start_x = theme.padding + door.coord.x as f32 * cell_size;
+            let start_y = theme.padding + door.coord.y as f32 * cell_size;
+            let (from, to) = if door.dir == NORTH {
+                ((start_x, start_y), (start_x + cell_size, start_y))
+            } else {
+                (
+                    (start_x + cell_size, start_y),
+                    (start_x + cell_size, start_y + cell_size),
+                )
+            };
 
-                for dir in 0..2 {
-                    if !maze.cells[i].paths[dir] {
-                        continue;
-                    }
+            canvas.display_list.add(
+                Drawing::new()
+                    .with_shape(Shape::Line {
+                        start: Point {
+                            x: from.0,
+                            y: from.1,
+                        },
+                        points: vec![LinePoint::Straight {
+                            point: Point { x: to.0, y: to.1 },
+                        }],
+                    })
+                    .with_style(Style::stroked(
+                        stroke_width(theme.wall_width * 2.0),
+                        rgb(door.color.rgb()),
+                    )),
+            );
+        }
 
-                    canvas.display_list.add(
-                        Drawing::new()
-                            .with_shape(Shape::Line {
-                                start: Point {
-                                    x: start_x
-                                        + (cell_size_f32 * line_map[dir][0])
-                                        + line_ending_adjustment[dir][0],
-                                    y: start_y
-                                        + (cell_size_f32 * line_map[dir][1])
-                                        + line_ending_adjustment[dir][1],
-                                },
-                                points: vec![LinePoint::Straight {
-                                    point: Point {
-                                        x: start_x
-                                            + (cell_size_f32 * line_map[dir][2])
-                                            + line_ending_adjustment[dir][2],
-                                        y: start_y
-                                            + (cell_size_f32 * line_map[dir][3])
-                                            + line_ending_adjustment[dir][3],
-                                    },
-                                }],
-                            })
-                            .with_style(Style::stroked(wall_thickness, Color::black())),
-                    );
-                }
-            }
+        for key in &maze.keys {
+            let (center_x, center_y) = cell_center(key.coord, theme);
+
+            canvas.display_list.add(
+                Drawing::new()
+                    .with_shape(Shape::Circle {
+                        radius: (cell_size / 4.0) as u32,
+                    })
+                    .with_xy(center_x, center_y)
+                    .with_style(Style::new(
+                        Fill::new(rgb(key.color.rgb())),
+                        Stroke::new(stroke_width(theme.wall_width / 2.0), rgb(theme.wall_color)),
+                    )),
+            );
         }
+    }
 
+    fn draw_solution(canvas: &mut Canvas, solution: &[Pair<usize>], theme: &Theme) {
         if !solution.is_empty() {
             for i in 0..solution.len() - 1 {
+                let (from_x, from_y) = cell_center(solution[i], theme);
+                let (to_x, to_y) = cell_center(solution[i + 1], theme);
+
                 canvas.display_list.add(
                     Drawing::new()
                         .with_shape(Shape::Line {
                             start: Point {
-                                x: (solution[i].x as f32 + 0.5) * cell_size_f32,
-                                y: (solution[i].y as f32 + 0.5) * cell_size_f32,
+                                x: from_x,
+                                y: from_y,
                             },
                             points: vec![LinePoint::Straight {
-                                point: Point {
-                                    x: (solution[i + 1].x as f32 + 0.5) * cell_size_f32,
-                                    y: (solution[i + 1].y as f32 + 0.5) * cell_size_f32,
-                                },
+                                point: Point { x: to_x, y: to_y },
                             }],
                         })
-                        .with_style(Style::stroked(wall_thickness, RGB::new(200, 40, 40))),
+                        .with_style(Style::stroked(
+                            stroke_width(theme.path_width),
+                            rgb(theme.path_color),
+                        )),
+                );
+            }
+        }
+
+        let markers = [
+            (solution.first(), theme.start_marker),
+            (solution.last(), theme.finish_marker),
+        ];
+        for (coord, color) in markers {
+            if let (Some(coord), Some(color)) = (coord, color) {
+                let (center_x, center_y) = cell_center(*coord, theme);
+
+                canvas.display_list.add(
+                    Drawing::new()
+                        .with_shape(Shape::Circle {
+                            radius: (theme.cell_size * 0.3) as u32,
+                        })
+                        .with_xy(center_x, center_y)
+                        .with_style(Style::filled(rgb(color))),
                 );
             }
         }
+    }
+}
+
+fn cell_center(coord: Pair<usize>, theme: &Theme) -> (f32, f32) {
+    (
+        theme.padding + (coord.x as f32 + 0.5) * theme.cell_size,
+        theme.padding + (coord.y as f32 + 0.5) * theme.cell_size,
+    )
+}
 
-        render::save(&canvas, "./mazey.svg", SvgRenderer::new()).expect("Image write has failed");
+// The `draw` crate only takes whole stroke widths.
+fn stroke_width(width: f32) -> u32 {
+    width.round().max(1.0) as u32
+}
+
+fn rgb((r, g, b): Rgb) -> RGB {
+    RGB::new(r, g, b)
+}
+
+fn svg_color((r, g, b): Rgb) -> String {
+    format!("rgb({},{},{})", r, g, b)
+}
+
+// Arc around the maze centre at radius `r`, from the current point at angle `alpha_from` to
+// `alpha_to`. A full circle is split in two, since an arc ending where it starts draws nothing.
+fn arc_to(
+    data: Data,
+    r: f32,
+    alpha_from: f32,
+    alpha_to: f32,
+    point: &impl Fn(f32, f32) -> (f32, f32),
+) -> Data {
+    let span = alpha_to - alpha_from;
+    if span.abs() >= 360.0 {
+        let alpha_half = alpha_from + span / 2.0;
+        return arc_to(
+            arc_to(data, r, alpha_from, alpha_half, point),
+            r,
+            alpha_half,
+            alpha_to,
+            point,
+        );
     }
+
+    // Angles grow counter-clockwise on screen, which is SVG's negative sweep direction.
+    let large_arc = if span.abs() > 180.0 { 1 } else { 0 };
+    let sweep = if span > 0.0 { 0 } else { 1 };
+    let (x, y) = point(r, alpha_to);
+
+    data.elliptical_arc_to((r, r, 0, large_arc, sweep, x, y))
 }
@@ -0,0 +1,521 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::circle_maze::CircleMaze;
+use crate::circle_maze_cell::CircleMazeCellDirection;
+use crate::theme::Theme;
+use crate::util::*;
+use crate::{Maze, Pair};
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_SOLUTION: &str = "\x1b[1;31m";
+
+#[derive(Debug, Clone)]
+pub struct TerminalOptions {
+    // Characters per cell, not counting the wall between cells.
+    pub cell_width: usize,
+    pub solution: char,
+    // Colours the solution and paints the distance heat map as cell backgrounds. Without it the
+    // heat map is left out.
+    pub color: bool,
+    // Source of the heat-map colours. Walls are box-drawing characters whatever the theme says.
+    pub theme: Theme,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        TerminalOptions {
+            cell_width: 3,
+            solution: '•',
+            color: true,
+            theme: Theme::dark(),
+        }
+    }
+}
+
+impl From<&Theme> for TerminalOptions {
+    fn from(theme: &Theme) -> Self {
+        TerminalOptions {
+            solution: theme.path_char,
+            theme: theme.clone(),
+            ..TerminalOptions::default()
+        }
+    }
+}
+
+// Walls laid out on a plain grid of `width` x `height` slots. `horizontal[y][x]` is the wall on top
+// of slot (x, y) and `vertical[y][x]` the one on its left, so both have an extra row or column for
+// the far edge. Slots map back to the maze cell they show; a cell may cover several slots.
+struct WallGrid {
+    width: usize,
+    height: usize,
+    horizontal: Vec<Vec<bool>>,
+    vertical: Vec<Vec<bool>>,
+    slots: Vec<Vec<Coord>>,
+    doors: HashMap<(Coord, usize), char>,
+    keys: HashMap<Coord, char>,
+}
+
+pub struct TerminalDrawer;
+
+impl TerminalDrawer {
+    #[allow(unused)]
+    pub fn draw(maze: &Maze, solution: &[Pair<usize>]) {
+        print!(
+            "{}",
+            TerminalDrawer::to_string(maze, solution, None, &TerminalOptions::default())
+        );
+    }
+
+    // `distance_map` is indexed [y][x], as built by `Solver::build_distance_map`.
+    #[allow(unused)]
+    pub fn to_string(
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        distance_map: Option<&[Vec<i32>]>,
+        options: &TerminalOptions,
+    ) -> String {
+        let mut output = String::new();
+        TerminalDrawer::render(&mut output, maze, solution, distance_map, options)
+            .expect("Writing to a String cannot fail");
+
+        output
+    }
+
+    #[allow(unused)]
+    pub fn render(
+        output: &mut impl fmt::Write,
+        maze: &Maze,
+        solution: &[Pair<usize>],
+        distance_map: Option<&[Vec<i32>]>,
+        options: &TerminalOptions,
+    ) -> fmt::Result {
+        if maze.width == 0 || maze.height == 0 {
+            return Ok(());
+        }
+
+        let grid = TerminalDrawer::maze_grid(maze);
+
+        TerminalDrawer::render_grid(output, &grid, solution, distance_map, options)
+    }
+
+    #[allow(unused)]
+    pub fn draw_circle_maze(maze: &CircleMaze, solution: &[Pair<usize>]) {
+        let options = TerminalOptions {
+            cell_width: 1,
+            ..TerminalOptions::default()
+        };
+
+        print!(
+            "{}",
+            TerminalDrawer::circle_maze_to_string(maze, solution, None, &options)
+        );
+    }
+
+    #[allow(unused)]
+    pub fn circle_maze_to_string(
+        maze: &CircleMaze,
+        solution: &[Pair<usize>],
+        distance_map: Option<&[Vec<i32>]>,
+        options: &TerminalOptions,
+    ) -> String {
+        let mut output = String::new();
+        TerminalDrawer::render_circle_maze(&mut output, maze, solution, distance_map, options)
+            .expect("Writing to a String cannot fail");
+
+        output
+    }
+
+    // The rings are cut open and laid out as rows, the outer ring on top and the centre at the
+    // bottom. Every ring is stretched to the width of the outer one, so a cell of an inner ring
+    // covers several columns. The left and right edges are the same cut.
+    #[allow(unused)]
+    pub fn render_circle_maze(
+        output: &mut impl fmt::Write,
+        maze: &CircleMaze,
+        solution: &[Pair<usize>],
+        distance_map: Option<&[Vec<i32>]>,
+        options: &TerminalOptions,
+    ) -> fmt::Result {
+        if maze.height == 0 {
+            return Ok(());
+        }
+
+        let grid = TerminalDrawer::circle_maze_grid(maze);
+
+        TerminalDrawer::render_grid(output, &grid, solution, distance_map, options)
+    }
+
+    fn maze_grid(maze: &Maze) -> WallGrid {
+        let cell = |x: usize, y: usize| &maze.cells[y * maze.width + x];
+
+        let mut horizontal = vec![vec![false; maze.width]; maze.height + 1];
+        let mut vertical = vec![vec![false; maze.width + 1]; maze.height];
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                horizontal[y][x] = cell(x, y).paths[NORTH];
+                vertical[y][x] = cell(x, y).paths[WEST];
+            }
+            vertical[y][maze.width] = cell(maze.width - 1, y).paths[EAST];
+        }
+        for (x, wall) in horizontal[maze.height].iter_mut().enumerate() {
+            *wall = cell(x, maze.height - 1).paths[SOUTH];
+        }
+
+        WallGrid {
+            width: maze.width,
+            height: maze.height,
+            horizontal,
+            vertical,
+            slots: (0..maze.height)
+                .map(|y| (0..maze.width).map(|x| Pair::new(x, y)).collect())
+                .collect(),
+            doors: maze
+                .doors
+                .iter()
+                .map(|door| {
+                    let letter = door.color.letter().to_ascii_uppercase();
+                    if door.dir == NORTH {
+                        ((door.coord, NORTH), letter)
+                    } else {
+                        ((Pair::new(door.coord.x + 1, door.coord.y), WEST), letter)
+                    }
+                })
+                .collect(),
+            keys: maze
+                .keys
+                .iter()
+                .map(|key| (key.coord, key.color.letter()))
+                .collect(),
+        }
+    }
+
+    fn circle_maze_grid(maze: &CircleMaze) -> WallGrid {
+        let width = maze.cells[maze.height - 1].len();
+        let height = maze.height;
+        // Grid rows run from the outer ring inwards.
+        let ring = |row: usize| maze.height - 1 - row;
+        let slot_coord = |row: usize, column: usize| {
+            let y = ring(row);
+            Pair::new(column / (width / maze.cells[y].len()), y)
+        };
+
+        let mut horizontal = vec![vec![true; width]; height + 1];
+        let mut vertical = vec![vec![false; width + 1]; height];
+        let mut slots = vec![];
+        for row in 0..height {
+            let y = ring(row);
+
+            for column in 0..width {
+                let coord = slot_coord(row, column);
+                let cell = &maze.cells[y][coord.x];
+
+                horizontal[row][column] = if row == 0 {
+                    !cell.is_open_at(CircleMazeCellDirection::North(0))
+                } else {
+                    let outer_coord = slot_coord(row - 1, column);
+                    !maze.cells[outer_coord.y][outer_coord.x]
+                        .is_open_at(CircleMazeCellDirection::South)
+                };
+
+                // The centre has no side walls, and a cell spread over several columns has none
+                // inside it.
+                if y > 0 && (column == 0 || slot_coord(row, column - 1) != coord) {
+                    vertical[row][column] = !cell.is_open_at(CircleMazeCellDirection::West);
+                }
+            }
+            vertical[row][width] = vertical[row][0];
+
+            slots.push((0..width).map(|column| slot_coord(row, column)).collect());
+        }
+
+        WallGrid {
+            width,
+            height,
+            horizontal,
+            vertical,
+            slots,
+            doors: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    fn render_grid(
+        output: &mut impl fmt::Write,
+        grid: &WallGrid,
+        solution: &[Pair<usize>],
+        distance_map: Option<&[Vec<i32>]>,
+        options: &TerminalOptions,
+    ) -> fmt::Result {
+        let cell_width = options.cell_width.max(1);
+        let on_solution: HashSet<Coord> = solution.iter().copied().collect();
+        let max_distance = distance_map
+            .and_then(|distance_map| distance_map.iter().flatten().copied().max())
+            .unwrap_or(0);
+        let background = |coord: Coord| -> Option<String> {
+            let distance = distance_map?[coord.y][coord.x];
+            if !options.color || distance < 0 {
+                return None;
+            }
+
+            let (r, g, b) = options.theme.heat_color(max_distance, distance)?;
+            Some(format!("\x1b[48;2;{};{};{}m", r, g, b))
+        };
+
+        for row in 0..=grid.height {
+            // Wall line above grid row `row`.
+            for column in 0..=grid.width {
+                let up = row > 0 && grid.vertical[row - 1][column];
+                let down = row < grid.height && grid.vertical[row][column];
+                let left = column > 0 && grid.horizontal[row][column - 1];
+                let right = column < grid.width && grid.horizontal[row][column];
+                write!(output, "{}", corner_glyph(up, right, down, left))?;
+
+                if column == grid.width {
+                    continue;
+                }
+                if grid.horizontal[row][column] {
+                    write!(output, "{}", "─".repeat(cell_width))?;
+                } else {
+                    let door = grid
+                        .slots
+                        .get(row)
+                        .and_then(|slots| grid.doors.get(&(slots[column], NORTH)));
+                    write!(output, "{}", centered(door.copied(), cell_width))?;
+                }
+            }
+            writeln!(output)?;
+
+            if row == grid.height {
+                break;
+            }
+
+            // Cells of grid row `row`, with the walls between them. Marks go in the middle column
+            // of each cell.
+            let slots = &grid.slots[row];
+            let mut mark_columns = vec![false; grid.width];
+            let mut first_column = 0;
+            for column in 1..=grid.width {
+                if column == grid.width || slots[column] != slots[first_column] {
+                    mark_columns[first_column + (column - first_column - 1) / 2] = true;
+                    first_column = column;
+                }
+            }
+
+            for column in 0..=grid.width {
+                let coord = slots[column.min(grid.width - 1)];
+
+                if grid.vertical[row][column] {
+                    write!(output, "│")?;
+                } else if column == 0 || column == grid.width {
+                    write!(output, " ")?;
+                } else if let Some(door) = grid.doors.get(&(coord, WEST)) {
+                    write!(output, "{}", door)?;
+                } else if slots[column - 1] == coord {
+                    // Inside a cell that covers several columns.
+                    match background(coord) {
+                        Some(background) => write!(output, "{} {}", background, ANSI_RESET)?,
+                        None => write!(output, " ")?,
+                    }
+                } else {
+                    write!(output, " ")?;
+                }
+
+                if column == grid.width {
+                    continue;
+                }
+
+                let mark = if !mark_columns[column] {
+                    None
+                } else if let Some(key) = grid.keys.get(&coord) {
+                    Some(key.to_string())
+                } else if on_solution.contains(&coord) {
+                    Some(if options.color {
+                        format!("{}{}{}", ANSI_SOLUTION, options.solution, ANSI_RESET)
+                    } else {
+                        options.solution.to_string()
+                    })
+                } else {
+                    None
+                };
+
+                let mark_offset = (cell_width - 1) / 2;
+                let background = background(coord);
+                if let Some(background) = &background {
+                    write!(output, "{}", background)?;
+                }
+                for i in 0..cell_width {
+                    match &mark {
+                        Some(mark) if i == mark_offset => {
+                            write!(output, "{}", mark)?;
+                            // The mark's own reset clears the background, so put it back.
+                            if let Some(background) = &background {
+                                write!(output, "{}", background)?;
+                            }
+                        }
+                        _ => write!(output, " ")?,
+                    }
+                }
+                if background.is_some() {
+                    write!(output, "{}", ANSI_RESET)?;
+                }
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Box-drawing character joining the walls that meet at a corner.
+fn corner_glyph(up: bool, right: bool, down: bool, left: bool) -> char {
+    match (up, right, down, left) {
+        (false, false, false, false) => ' ',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╶',
+        (false, false, true, false) => '╷',
+        (false, false, false, true) => '╴',
+        (true, false, true, false) => '│',
+        (false, true, false, true) => '─',
+        (false, true, true, false) => '┌',
+        (false, false, true, true) => '┐',
+        (true, true, false, false) => '└',
+        (true, false, false, true) => '┘',
+        (true, true, true, false) => '├',
+        (true, false, true, true) => '┤',
+        (false, true, true, true) => '┬',
+        (true, true, false, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+fn centered(mark: Option<char>, width: usize) -> String {
+    let mark_offset = (width - 1) / 2;
+
+    (0..width)
+        .map(|i| match mark {
+            Some(mark) if i == mark_offset => mark,
+            _ => ' ',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opening::OpeningKind;
+
+    fn plain_options() -> TerminalOptions {
+        TerminalOptions {
+            cell_width: 1,
+            color: false,
+            ..TerminalOptions::default()
+        }
+    }
+
+    #[test]
+    fn draws_nothing_for_empty_mazes() {
+        let options = TerminalOptions::default();
+
+        assert_eq!(
+            TerminalDrawer::to_string(&Maze::new_full(0, 3), &[], None, &options),
+            ""
+        );
+        assert_eq!(
+            TerminalDrawer::to_string(&Maze::new_full(3, 0), &[], None, &options),
+            ""
+        );
+        assert_eq!(
+            TerminalDrawer::circle_maze_to_string(&CircleMaze::new(0), &[], None, &options),
+            ""
+        );
+    }
+
+    #[test]
+    fn joins_walls_with_box_drawing_corners() {
+        let output = TerminalDrawer::to_string(&Maze::new_full(2, 2), &[], None, &plain_options());
+
+        assert_eq!(
+            output,
+            "┌─┬─┐\n\
+             │ │ │\n\
+             ├─┼─┤\n\
+             │ │ │\n\
+             └─┴─┘\n"
+        );
+    }
+
+    #[test]
+    fn draws_openings_and_solution_in_wide_cells() {
+        // Right along the top row and back along the bottom one, open on the west side of both.
+        let mut maze = Maze::new_full(3, 2);
+        maze.connect_cells(0, 0, EAST);
+        maze.connect_cells(1, 0, EAST);
+        maze.connect_cells(2, 0, SOUTH);
+        maze.connect_cells(1, 1, EAST);
+        maze.connect_cells(0, 1, EAST);
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 0), WEST)
+            .unwrap();
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(0, 1), WEST)
+            .unwrap();
+        let solution: Vec<Pair<usize>> = [(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1)]
+            .iter()
+            .map(|(x, y)| Pair::new(*x, *y))
+            .collect();
+        let options = TerminalOptions {
+            color: false,
+            ..TerminalOptions::default()
+        };
+
+        assert_eq!(
+            TerminalDrawer::to_string(&maze, &solution, None, &options),
+            "╶───────────┐\n\
+             \x20 •   •   • │\n\
+             ╶───────╴   │\n\
+             \x20 •   •   • │\n\
+             ╶───────────┘\n"
+        );
+    }
+
+    #[test]
+    fn colours_the_solution_and_the_heat_map() {
+        let mut maze = Maze::new_full(2, 1);
+        maze.connect_cells(0, 0, EAST);
+        let distance_map = vec![vec![0, 1]];
+        let options = TerminalOptions {
+            cell_width: 1,
+            ..TerminalOptions::default()
+        };
+
+        let near = "\x1b[48;2;51;212;51m";
+        let far = "\x1b[48;2;0;0;0m";
+        assert_eq!(
+            TerminalDrawer::to_string(&maze, &[Pair::new(0, 0)], Some(&distance_map), &options),
+            format!(
+                "┌───┐\n│{near}{ANSI_SOLUTION}•{ANSI_RESET}{near}{ANSI_RESET} {far} {ANSI_RESET}│\n└───┘\n"
+            )
+        );
+    }
+
+    #[test]
+    fn unrolls_circle_mazes_outer_ring_first() {
+        // The centre joined to every cell around it, two of which are also joined to each other.
+        let mut maze = CircleMaze::new(2);
+        for n in 0..6 {
+            maze.connect_cells(Pair::new(0, 0), CircleMazeCellDirection::North(n));
+        }
+        maze.connect_cells(Pair::new(2, 1), CircleMazeCellDirection::East);
+        maze.add_opening("exit", OpeningKind::Exit, Pair::new(4, 1))
+            .unwrap();
+        let solution = [Pair::new(0, 0), Pair::new(4, 1)];
+
+        assert_eq!(
+            TerminalDrawer::circle_maze_to_string(&maze, &solution, None, &plain_options()),
+            "┌─┬─┬───┐ ┌─┐\n\
+             │ │ │   │•│ │\n\
+             ╵ ╵ ╵   ╵ ╵ ╵\n\
+             \x20    •       \n\
+             ╶───────────╴\n"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+pub type Rgb = (u8, u8, u8);
+
+// Look of a drawing, shared by all drawers. Sizes are in drawing units, which are pixels for SVG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    // Left transparent when missing.
+    pub background: Option<Rgb>,
+    pub wall_color: Rgb,
+    pub wall_width: f32,
+    pub path_color: Rgb,
+    pub path_width: f32,
+    pub cell_size: f32,
+    // Space around the maze.
+    pub padding: f32,
+    // Heat-map colours from the start out to the farthest cell, blended evenly in between.
+    pub heat_map: Vec<Rgb>,
+    // Dots on the first and the last cell of the path.
+    pub start_marker: Option<Rgb>,
+    pub finish_marker: Option<Rgb>,
+    // Characters for the text drawers.
+    pub wall_char: char,
+    pub path_char: char,
+}
+
+impl Theme {
+    // Dark lines on white that do not use up much ink.
+    #[allow(unused)]
+    pub fn print() -> Theme {
+        Theme {
+            background: Some((255, 255, 255)),
+            wall_color: (0, 0, 0),
+            wall_width: 2.0,
+            path_color: (200, 40, 40),
+            path_width: 2.0,
+            cell_size: 20.0,
+            padding: 10.0,
+            heat_map: vec![(230, 245, 230), (120, 175, 120)],
+            start_marker: Some((40, 150, 60)),
+            finish_marker: Some((40, 90, 220)),
+            wall_char: '█',
+            path_char: 'x',
+        }
+    }
+
+    // Colours and sizes of the original flo_draw window, with its square maze wall colour used for
+    // circle mazes too (those had lighter walls) and start and finish markers added.
+    #[allow(unused)]
+    pub fn dark() -> Theme {
+        Theme {
+            background: Some((26, 26, 26)),
+            wall_color: (128, 153, 179),
+            wall_width: 6.0,
+            path_color: (255, 102, 26),
+            path_width: 6.0,
+            cell_size: 16.0,
+            padding: 8.0,
+            heat_map: vec![(51, 212, 51), (0, 0, 0)],
+            start_marker: Some((90, 200, 255)),
+            finish_marker: Some((255, 200, 60)),
+            wall_char: '█',
+            path_char: 'x',
+        }
+    }
+
+    // Thick white walls on black, with a heat map kept dark so the walls stand out.
+    #[allow(unused)]
+    pub fn high_contrast() -> Theme {
+        Theme {
+            background: Some((0, 0, 0)),
+            wall_color: (255, 255, 255),
+            wall_width: 4.0,
+            path_color: (255, 230, 0),
+            path_width: 4.0,
+            cell_size: 24.0,
+            padding: 12.0,
+            heat_map: vec![(0, 40, 160), (150, 0, 120)],
+            start_marker: Some((0, 255, 0)),
+            finish_marker: Some((255, 0, 255)),
+            wall_char: '#',
+            path_char: '*',
+        }
+    }
+
+    // Height of a circle maze ring, the same for every drawer. Rings are two and a half cells high,
+    // which keeps the cells of the inner rings from getting cramped.
+    pub fn ring_height(&self) -> f32 {
+        self.cell_size * 2.5
+    }
+
+    // Heat-map colour of a cell `distance` steps from the start. Unreached cells have none.
+    pub fn heat_color(&self, max_distance: i32, distance: i32) -> Option<Rgb> {
+        let first_stop = *self.heat_map.first()?;
+        if distance < 0 {
+            return None;
+        }
+        if max_distance <= 0 || self.heat_map.len() == 1 {
+            return Some(first_stop);
+        }
+
+        let position =
+            (distance as f32 / max_distance as f32).min(1.0) * (self.heat_map.len() - 1) as f32;
+        let i = (position as usize).min(self.heat_map.len() - 2);
+        let t = position - i as f32;
+        let (from, to) = (self.heat_map[i], self.heat_map[i + 1]);
+        let blend = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+
+        Some((
+            blend(from.0, to.0),
+            blend(from.1, to.1),
+            blend(from.2, to.2),
+        ))
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::circle_maze::CircleMaze;
+use crate::circle_maze_cell::CircleMazeCellDirection;
+use crate::util::*;
+use crate::{Maze, Pair};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    // `coord` is open towards `neighbour`, but `neighbour` is walled off towards `coord`.
+    AsymmetricWall { coord: Coord, neighbour: Coord },
+    // The passage between the two cells closes a loop, so the maze is not perfect.
+    Loop { coord: Coord, neighbour: Coord },
+    // The cell cannot be reached from the first cell of the maze.
+    Unreachable(Coord),
+    // The outer wall is open without a matching entry in `openings`.
+    OpenToOutside { coord: Coord, dir: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::AsymmetricWall { coord, neighbour } => write!(
+                f,
+                "cell ({}, {}) is open towards ({}, {}) but not the other way around",
+                coord.x, coord.y, neighbour.x, neighbour.y
+            ),
+            Violation::Loop { coord, neighbour } => write!(
+                f,
+                "passage between ({}, {}) and ({}, {}) closes a loop",
+                coord.x, coord.y, neighbour.x, neighbour.y
+            ),
+            Violation::Unreachable(coord) => {
+                write!(f, "cell ({}, {}) is unreachable", coord.x, coord.y)
+            }
+            Violation::OpenToOutside { coord, dir } => write!(
+                f,
+                "cell ({}, {}) is open to the outside on side {} without an opening",
+                coord.x, coord.y, dir
+            ),
+        }
+    }
+}
+
+// Wall between two neighbouring cells, seen from both sides.
+struct Passage {
+    coord: Coord,
+    neighbour: Coord,
+    open_from_coord: bool,
+    open_from_neighbour: bool,
+}
+
+pub struct Validator;
+
+impl Validator {
+    // An empty list means the walls are consistent and the maze is perfect: every cell is
+    // reachable through exactly one route.
+    #[allow(unused)]
+    pub fn validate_maze(maze: &Maze) -> Vec<Violation> {
+        let mut coords: Vec<Coord> = vec![];
+        let mut passages: Vec<Passage> = vec![];
+        let mut violations: Vec<Violation> = vec![];
+
+        for y in 0..maze.height {
+            for x in 0..maze.width {
+                let coord = Pair::new(x, y);
+                let cell = &maze.cells[coord.index(maze.width)];
+                let neighbours = maze.neighbours(coord, CellReachType::Anything);
+                coords.push(coord);
+
+                for dir in 0..4 {
+                    // Sides without a neighbour face the outside of the maze.
+                    if !cell.paths[dir] && !neighbours.contains_key(&dir) {
+                        let is_registered = maze
+                            .openings
+                            .iter()
+                            .any(|opening| opening.coord == coord && opening.dir == dir);
+                        if !is_registered {
+                            violations.push(Violation::OpenToOutside { coord, dir });
+                        }
+                    }
+                }
+
+                // East and south only, so every passage is listed once.
+                for dir in [EAST, SOUTH] {
+                    if let Some(neighbour) = neighbours.get(&dir) {
+                        passages.push(Passage {
+                            coord,
+                            neighbour: *neighbour,
+                            open_from_coord: !cell.paths[dir],
+                            open_from_neighbour: !maze.cells[neighbour.index(maze.width)].paths
+                                [(dir + 2) % 4],
+                        });
+                    }
+                }
+            }
+        }
+
+        violations.extend(Validator::validate_passages(&coords, &passages));
+        violations
+    }
+
+    #[allow(unused)]
+    pub fn validate_circle_maze(maze: &CircleMaze) -> Vec<Violation> {
+        let mut coords: Vec<Coord> = vec![];
+        let mut passages: Vec<Passage> = vec![];
+        let mut violations: Vec<Violation> = vec![];
+
+        for y in 0..maze.height {
+            for x in 0..maze.cells[y].len() {
+                let coord = Pair::new(x, y);
+                let cell = &maze.cells[y][x];
+                coords.push(coord);
+
+                if y == maze.height - 1 && cell.is_open_at(CircleMazeCellDirection::North(0)) {
+                    let is_registered = maze.openings.iter().any(|opening| opening.coord == coord);
+                    if !is_registered {
+                        violations.push(Violation::OpenToOutside { coord, dir: NORTH });
+                    }
+                }
+
+                // Passages towards the centre are listed by the inner cell instead, through its
+                // outward side, and passages to the west by the western neighbour.
+                for (dir, neighbour) in maze.neighbours(coord, CellReachType::Anything) {
+                    if dir == CircleMazeCellDirection::South || dir == CircleMazeCellDirection::West
+                    {
+                        continue;
+                    }
+
+                    passages.push(Passage {
+                        coord,
+                        neighbour,
+                        open_from_coord: cell.is_open_at(dir),
+                        open_from_neighbour: maze.cells[neighbour.y][neighbour.x]
+                            .is_open_at(maze.opposite_direction(coord, dir)),
+                    });
+                }
+            }
+        }
+
+        violations.extend(Validator::validate_passages(&coords, &passages));
+        violations
+    }
+
+    fn validate_passages(coords: &[Coord], passages: &[Passage]) -> Vec<Violation> {
+        let mut violations: Vec<Violation> = vec![];
+
+        let indices: HashMap<Coord, usize> = coords
+            .iter()
+            .enumerate()
+            .map(|(i, coord)| (*coord, i))
+            .collect();
+        // Union-find over cells joined by two-sided passages.
+        let mut parents: Vec<usize> = (0..coords.len()).collect();
+
+        for passage in passages {
+            if passage.open_from_coord != passage.open_from_neighbour {
+                violations.push(if passage.open_from_coord {
+                    Violation::AsymmetricWall {
+                        coord: passage.coord,
+                        neighbour: passage.neighbour,
+                    }
+                } else {
+                    Violation::AsymmetricWall {
+                        coord: passage.neighbour,
+                        neighbour: passage.coord,
+                    }
+                });
+                continue;
+            }
+
+            if !passage.open_from_coord {
+                continue;
+            }
+
+            let root = Validator::find_root(&mut parents, indices[&passage.coord]);
+            let neighbour_root = Validator::find_root(&mut parents, indices[&passage.neighbour]);
+            if root == neighbour_root {
+                violations.push(Violation::Loop {
+                    coord: passage.coord,
+                    neighbour: passage.neighbour,
+                });
+            } else {
+                parents[neighbour_root] = root;
+            }
+        }
+
+        if !coords.is_empty() {
+            let first_root = Validator::find_root(&mut parents, 0);
+            for (i, coord) in coords.iter().enumerate() {
+                if Validator::find_root(&mut parents, i) != first_root {
+                    violations.push(Violation::Unreachable(*coord));
+                }
+            }
+        }
+
+        violations
+    }
+
+    fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+
+        i
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze_builder::MazeBuilder;
+    use crate::opening::OpeningKind;
+
+    #[test]
+    fn accepts_carved_mazes() {
+        let mut maze = Maze::new_full(9, 7);
+        MazeBuilder::parallel_tiled_maze_creation(&mut maze, 4, 3).unwrap();
+        maze.add_opening("entrance", OpeningKind::Entrance, Pair::new(0, 0), WEST)
+            .unwrap();
+
+        assert_eq!(Validator::validate_maze(&maze), vec![]);
+    }
+
+    #[test]
+    fn reports_one_sided_walls() {
+        let mut maze = Maze::new_full(2, 1);
+        maze.cells[1].paths[WEST] = false;
+
+        assert_eq!(
+            Validator::validate_maze(&maze),
+            vec![
+                Violation::AsymmetricWall {
+                    coord: Pair::new(1, 0),
+                    neighbour: Pair::new(0, 0),
+                },
+                Violation::Unreachable(Pair::new(1, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_loops() {
+        let mut maze = Maze::new_full(2, 2);
+        maze.connect_cells(0, 0, EAST);
+        maze.connect_cells(0, 0, SOUTH);
+        maze.connect_cells(1, 0, SOUTH);
+        maze.connect_cells(0, 1, EAST);
+
+        assert_eq!(
+            Validator::validate_maze(&maze),
+            vec![Violation::Loop {
+                coord: Pair::new(0, 1),
+                neighbour: Pair::new(1, 1),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_unreachable_cells() {
+        let mut maze = Maze::new_full(3, 1);
+        maze.connect_cells(0, 0, EAST);
+
+        assert_eq!(
+            Validator::validate_maze(&maze),
+            vec![Violation::Unreachable(Pair::new(2, 0))]
+        );
+    }
+
+    #[test]
+    fn reports_unregistered_openings() {
+        let mut maze = Maze::new_full(2, 1);
+        maze.connect_cells(0, 0, EAST);
+        maze.cells[1].paths[SOUTH] = false;
+
+        assert_eq!(
+            Validator::validate_maze(&maze),
+            vec![Violation::OpenToOutside {
+                coord: Pair::new(1, 0),
+                dir: SOUTH,
+            }]
+        );
+    }
+
+    #[test]
+    fn validates_circle_mazes() {
+        // The centre joined to each of the six cells around it.
+        let mut maze = CircleMaze::new(2);
+        for n in 0..6 {
+            maze.connect_cells(Pair::new(0, 0), CircleMazeCellDirection::North(n));
+        }
+        assert_eq!(Validator::validate_circle_maze(&maze), vec![]);
+
+        maze.connect_cells(Pair::new(0, 1), CircleMazeCellDirection::East);
+        assert_eq!(
+            Validator::validate_circle_maze(&maze),
+            vec![Violation::Loop {
+                coord: Pair::new(0, 1),
+                neighbour: Pair::new(1, 1),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_one_sided_circle_maze_walls() {
+        let mut maze = CircleMaze::new(2);
+        for n in 0..5 {
+            maze.connect_cells(Pair::new(0, 0), CircleMazeCellDirection::North(n));
+        }
+        maze.cells[1][5].open(CircleMazeCellDirection::West);
+
+        assert_eq!(
+            Validator::validate_circle_maze(&maze),
+            vec![
+                Violation::AsymmetricWall {
+                    coord: Pair::new(5, 1),
+                    neighbour: Pair::new(4, 1),
+                },
+                Violation::Unreachable(Pair::new(5, 1)),
+            ]
+        );
+    }
+}